@@ -0,0 +1,60 @@
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+
+// Axis-aligned bounding box
+#[derive(Debug, Copy, Clone)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3
+}
+
+impl Aabb {
+    pub fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> bool {
+        self.hit_interval(r, t_min, t_max).is_some()
+    }
+
+    // Interval of t in the box by the slab test
+    pub fn hit_interval(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<(f32, f32)> {
+        let mut t_min: f32 = t_min;
+        let mut t_max: f32 = t_max;
+        for axis in 0..3 {
+            let inv_d: f32 = 1.0 / r.direction[axis];
+            let mut t0: f32 = (self.min[axis] - r.origin[axis]) * inv_d;
+            let mut t1: f32 = (self.max[axis] - r.origin[axis]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            // NOTE: Written in this way so that NaN (0 * inf) does not reject the box
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
+            if t_max < t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+
+    pub fn surrounding_box(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: Vec3 {
+                x: self.min.x.min(other.min.x),
+                y: self.min.y.min(other.min.y),
+                z: self.min.z.min(other.min.z)
+            },
+            max: Vec3 {
+                x: self.max.x.max(other.max.x),
+                y: self.max.y.max(other.max.y),
+                z: self.max.z.max(other.max.z)
+            }
+        }
+    }
+
+    pub fn centroid(&self) -> Vec3 {
+        &(&self.min + &self.max) * 0.5
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        let d: Vec3 = &self.max - &self.min;
+        2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+}
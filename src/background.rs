@@ -0,0 +1,179 @@
+use rand::prelude::*;
+use crate::color3::Color3;
+use crate::vec3::Vec3;
+use crate::cylinder_hitable::azimuth;
+
+// Light coming from directions where rays escape the scene
+pub trait Background: Send {
+    // Radiance from the direction (not necessarily a unit vector)
+    fn color(&self, direction: &Vec3) -> Color3;
+    // Density over solid angle of directions chosen by `random` (0 if the background is not sampled)
+    fn pdf_value(&self, _direction: &Vec3) -> f32 {
+        0.0
+    }
+    // Random unit direction towards bright parts of the background (None if it is not sampled as a light)
+    fn random(&self, _rng: &mut rand::rngs::StdRng) -> Option<Vec3> {
+        None
+    }
+}
+
+// Vertical gradient from the bottom color straight down to the top color straight up
+pub struct GradientBackground {
+    pub bottom: Color3,
+    pub top: Color3
+}
+
+impl GradientBackground {
+    // White to light blue sky
+    pub fn sky() -> GradientBackground {
+        GradientBackground {
+            bottom: Color3 {r: 1.0, g: 1.0, b: 1.0},
+            top: Color3 {r: 0.5, g: 0.7, b: 1.0}
+        }
+    }
+}
+
+impl Background for GradientBackground {
+    fn color(&self, direction: &Vec3) -> Color3 {
+        let unit_direction: Vec3 = direction.unit_vector();
+        let t: f32 = 0.5 * (unit_direction.y + 1.0);
+        &(&self.bottom * (1.0 - t)) + &(&self.top * t)
+    }
+}
+
+pub struct SolidBackground {
+    pub color: Color3
+}
+
+impl Background for SolidBackground {
+    fn color(&self, _direction: &Vec3) -> Color3 {
+        self.color
+    }
+}
+
+// Normalized cumulative distribution of the weights starting at 0 (uniform if all weights are 0)
+fn cumulative_distribution(weights: &[f64]) -> Vec<f32> {
+    let total: f64 = weights.iter().sum();
+    let mut sum: f64 = 0.0;
+    let mut cdf: Vec<f32> = vec![0.0];
+    for (i, w) in weights.iter().enumerate() {
+        sum += w;
+        cdf.push(if total > 0.0 { (sum / total) as f32 } else { (i + 1) as f32 / weights.len() as f32 });
+    }
+    cdf
+}
+
+// Bin of the cumulative distribution where a uniform random number in [0, 1) falls
+// NOTE: Bins of zero probability are never chosen
+fn sample_bin(cdf: &[f32], x: f32) -> usize {
+    cdf[1..].partition_point(|&c| c <= x).min(cdf.len() - 2)
+}
+
+// Rotation of the direction around the Y axis by the angle in radians
+fn rotate_y(v: &Vec3, angle: f32) -> Vec3 {
+    let (sin, cos) = angle.sin_cos();
+    Vec3 {x: cos * v.x + sin * v.z, y: v.y, z: -sin * v.x + cos * v.z}
+}
+
+// Equirectangular (latitude-longitude) image around the scene with the top row straight up,
+// whose horizontal coordinate is the azimuth around the Y axis (as in sphere_uv)
+// NOTE: Pixels are sampled by their luminance times the solid angle they cover so that bright spots
+// such as the sun are found by next event estimation
+pub struct EnvironmentMap {
+    width: u32,
+    height: u32,
+    // Linear colors (top row first)
+    pixels: Vec<Color3>,
+    // Angle in radians the image is turned around the Y axis
+    rotation: f32,
+    intensity: f32,
+    // Cumulative distribution of rows, and the one of pixels in each row (width + 1 values per row)
+    row_cdf: Vec<f32>,
+    column_cdfs: Vec<f32>,
+    // Whether any pixel has light to sample
+    sampled: bool
+}
+
+impl EnvironmentMap {
+    // `rotation` is in degrees
+    pub fn new(width: u32, height: u32, pixels: Vec<Color3>, rotation: f32, intensity: f32) -> EnvironmentMap {
+        let w: usize = width as usize;
+        let mut row_weights: Vec<f64> = Vec::new();
+        let mut column_cdfs: Vec<f32> = Vec::new();
+        for (j, row) in pixels.chunks(w.max(1)).enumerate() {
+            let sin_theta: f64 = (std::f64::consts::PI * (j as f64 + 0.5) / height as f64).sin();
+            let weights: Vec<f64> = row.iter()
+                .map(|c| (0.2126 * c.r + 0.7152 * c.g + 0.0722 * c.b).max(0.0) as f64 * sin_theta)
+                .collect();
+            row_weights.push(weights.iter().sum());
+            column_cdfs.extend(cumulative_distribution(&weights));
+        }
+        let sampled: bool = row_weights.iter().any(|&w| w > 0.0);
+        EnvironmentMap {
+            width,
+            height,
+            pixels,
+            rotation: rotation.to_radians(),
+            intensity,
+            row_cdf: cumulative_distribution(&row_weights),
+            column_cdfs,
+            sampled
+        }
+    }
+
+    // Pixel in the direction of the map and the sine of the polar angle of the direction
+    fn pixel(&self, direction: &Vec3) -> (usize, usize, f32) {
+        let d: Vec3 = rotate_y(&direction.unit_vector(), -self.rotation);
+        let y: f32 = d.y.clamp(-1.0, 1.0);
+        let u: f32 = azimuth(d.x, d.z);
+        let v: f32 = y.acos() / std::f32::consts::PI;
+        let i: usize = ((u * self.width as f32) as usize).min(self.width as usize - 1);
+        let j: usize = ((v * self.height as f32) as usize).min(self.height as usize - 1);
+        (i, j, (1.0 - y * y).max(0.0).sqrt())
+    }
+
+    fn column_cdf(&self, j: usize) -> &[f32] {
+        let n: usize = self.width as usize + 1;
+        &self.column_cdfs[j * n..(j + 1) * n]
+    }
+}
+
+impl Background for EnvironmentMap {
+    fn color(&self, direction: &Vec3) -> Color3 {
+        if self.pixels.is_empty() {
+            return Color3 {r: 0.0, g: 0.0, b: 0.0};
+        }
+        let (i, j, _) = self.pixel(direction);
+        &self.pixels[j * self.width as usize + i] * self.intensity
+    }
+
+    fn pdf_value(&self, direction: &Vec3) -> f32 {
+        if !self.sampled {
+            return 0.0;
+        }
+        let (i, j, sin_theta) = self.pixel(direction);
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+        let column_cdf: &[f32] = self.column_cdf(j);
+        let probability: f32 = (self.row_cdf[j + 1] - self.row_cdf[j]) * (column_cdf[i + 1] - column_cdf[i]);
+        // Density over the image by the area of the unit square mapped to solid angle (2 pi^2 sin theta)
+        let pi: f32 = std::f32::consts::PI;
+        probability * (self.width * self.height) as f32 / (2.0 * pi * pi * sin_theta)
+    }
+
+    fn random(&self, rng: &mut rand::rngs::StdRng) -> Option<Vec3> {
+        if !self.sampled {
+            return None;
+        }
+        let j: usize = sample_bin(&self.row_cdf, rng.gen::<f32>());
+        let i: usize = sample_bin(self.column_cdf(j), rng.gen::<f32>());
+        // Uniformly in the pixel
+        let u: f32 = (i as f32 + rng.gen::<f32>()) / self.width as f32;
+        let v: f32 = (j as f32 + rng.gen::<f32>()) / self.height as f32;
+        let phi: f32 = 2.0 * std::f32::consts::PI * u - std::f32::consts::PI;
+        let (sin_theta, cos_theta) = (std::f32::consts::PI * v).sin_cos();
+        let d: Vec3 = Vec3 {x: sin_theta * phi.cos(), y: cos_theta, z: -sin_theta * phi.sin()};
+        Some(rotate_y(&d, self.rotation))
+    }
+}
@@ -0,0 +1,62 @@
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::hitable::Hitable;
+use crate::hitable::HitRecord;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::rect_hitable::AxisAlignedRect;
+use core::borrow::Borrow;
+
+// Axis-aligned box made of six rectangles sharing the material, with normals facing outwards
+pub struct BoxHitable {
+    min: Vec3,
+    max: Vec3,
+    // Rectangles and whether each one faces the negative direction of its axis
+    sides: Vec<(AxisAlignedRect, bool)>,
+    material: Box<dyn Material + std::marker::Sync>
+}
+
+impl BoxHitable {
+    pub fn new(min: Vec3, max: Vec3, material: Box<dyn Material + std::marker::Sync>) -> BoxHitable {
+        let mut sides: Vec<(AxisAlignedRect, bool)> = Vec::with_capacity(6);
+        for &axes in &[(0, 1, 2), (0, 2, 1), (1, 2, 0)] {
+            let (axis_a, axis_b, axis_c) = axes;
+            for &(k, faces_negative) in &[(min[axis_c], true), (max[axis_c], false)] {
+                sides.push((AxisAlignedRect {
+                    axes,
+                    a0: min[axis_a],
+                    a1: max[axis_a],
+                    b0: min[axis_b],
+                    b1: max[axis_b],
+                    k
+                }, faces_negative));
+            }
+        }
+        BoxHitable {min, max, sides, material}
+    }
+}
+
+impl Hitable for BoxHitable {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let mut closest_so_far: f32 = t_max;
+        let mut hit_record_opt: Option<HitRecord> = None;
+        for (rect, faces_negative) in &self.sides {
+            if let Some((t, u, v)) = rect.hit(r, t_min, closest_so_far) {
+                closest_so_far = t;
+                hit_record_opt = Some(HitRecord {
+                    t,
+                    p: r.point_at_parameter(t),
+                    normal: if *faces_negative { -&rect.normal() } else { rect.normal() },
+                    u,
+                    v,
+                    material: self.material.borrow()
+                });
+            }
+        }
+        hit_record_opt
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb {min: self.min, max: self.max})
+    }
+}
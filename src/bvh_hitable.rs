@@ -0,0 +1,286 @@
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use crate::hitable::Hitable;
+use crate::hitable::HitRecord;
+use crate::list_hitable::ListHitable;
+use crate::aabb::Aabb;
+
+// Bounding volume hierarchy built by the surface area heuristic (SAH) over primitives referred by indices
+pub struct Bvh {
+    // Nodes in depth-first order (the left child of an interior node is the next node)
+    nodes: Vec<BvhNode>,
+    // Primitive indices ordered so that each leaf refers to a contiguous range
+    indices: Vec<usize>
+}
+
+// BVH of hitables
+pub struct BvhHitable {
+    bvh: Bvh,
+    bounded: Vec<Box<dyn Hitable + Sync>>,
+    // Hitables without bounding boxes, which are tested linearly
+    unbounded: ListHitable
+}
+
+enum BvhNode {
+    Leaf {
+        bbox: Aabb,
+        start: usize,
+        count: usize
+    },
+    Interior {
+        bbox: Aabb,
+        right: usize,
+        axis: usize
+    }
+}
+
+impl BvhNode {
+    fn bbox(&self) -> &Aabb {
+        match self {
+            BvhNode::Leaf {bbox, ..} => bbox,
+            BvhNode::Interior {bbox, ..} => bbox
+        }
+    }
+}
+
+struct BuildItem {
+    bbox: Aabb,
+    centroid: Vec3,
+    // Primitive index
+    index: usize
+}
+
+#[derive(Copy, Clone)]
+struct Bin {
+    bbox: Option<Aabb>,
+    count: usize
+}
+
+fn union_opt(a: Option<Aabb>, b: &Aabb) -> Aabb {
+    match a {
+        Some(a) => a.surrounding_box(b),
+        None => *b
+    }
+}
+
+impl Bvh {
+    const N_BINS: usize = 16;
+    const MAX_LEAF_SIZE: usize = 4;
+    const TRAVERSAL_COST: f32 = 1.0;
+    const INTERSECTION_COST: f32 = 1.0;
+
+    // Build a BVH from bounding boxes of primitives
+    pub fn new(bboxes: &[Aabb]) -> Bvh {
+        let mut items: Vec<BuildItem> = bboxes.iter().enumerate()
+            .map(|(index, bbox)| BuildItem {bbox: *bbox, centroid: bbox.centroid(), index})
+            .collect();
+        let mut nodes: Vec<BvhNode> = Vec::new();
+        if !items.is_empty() {
+            Self::build(&mut nodes, &mut items, 0);
+        }
+        Bvh {
+            nodes,
+            indices: items.iter().map(|item| item.index).collect()
+        }
+    }
+
+    // Build nodes recursively and return the index of the created node
+    // (`offset` is the position of `items` in the whole item list)
+    fn build(nodes: &mut Vec<BvhNode>, items: &mut [BuildItem], offset: usize) -> usize {
+        let bbox: Aabb = items.iter().skip(1).fold(items[0].bbox, |b, item| b.surrounding_box(&item.bbox));
+        let node_idx: usize = nodes.len();
+        let leaf = BvhNode::Leaf {bbox, start: offset, count: items.len()};
+
+        if items.len() == 1 {
+            nodes.push(leaf);
+            return node_idx;
+        }
+
+        // Bounds of centroids to choose bins
+        let centroid_bbox: Aabb = items.iter().skip(1).fold(
+            Aabb {min: items[0].centroid, max: items[0].centroid},
+            |b, item| b.surrounding_box(&Aabb {min: item.centroid, max: item.centroid})
+        );
+
+        // Find the best split over all axes by binned SAH
+        let mut best: Option<(f32, usize, usize)> = None; // (cost, axis, bin)
+        for axis in 0..3 {
+            let c_min: f32 = centroid_bbox.min[axis];
+            let c_max: f32 = centroid_bbox.max[axis];
+            if c_max - c_min <= 0.0 {
+                continue;
+            }
+            let mut bins: [Bin; Self::N_BINS] = [Bin {bbox: None, count: 0}; Self::N_BINS];
+            for item in items.iter() {
+                let b: usize = Self::bin_index(item.centroid[axis], c_min, c_max);
+                bins[b].bbox = Some(union_opt(bins[b].bbox, &item.bbox));
+                bins[b].count += 1;
+            }
+            // Sweep from the right to accumulate areas and counts
+            let mut right_areas: [f32; Self::N_BINS] = [0.0; Self::N_BINS];
+            let mut right_counts: [usize; Self::N_BINS] = [0; Self::N_BINS];
+            let mut right_bbox: Option<Aabb> = None;
+            let mut right_count: usize = 0;
+            for b in (1..Self::N_BINS).rev() {
+                if let Some(bin_bbox) = &bins[b].bbox {
+                    right_bbox = Some(union_opt(right_bbox, bin_bbox));
+                }
+                right_count += bins[b].count;
+                right_areas[b] = right_bbox.map_or(0.0, |b| b.surface_area());
+                right_counts[b] = right_count;
+            }
+            // Sweep from the left and evaluate the cost of splitting before each bin
+            let mut left_bbox: Option<Aabb> = None;
+            let mut left_count: usize = 0;
+            for b in 1..Self::N_BINS {
+                if let Some(bin_bbox) = &bins[b - 1].bbox {
+                    left_bbox = Some(union_opt(left_bbox, bin_bbox));
+                }
+                left_count += bins[b - 1].count;
+                if left_count == 0 || right_counts[b] == 0 {
+                    continue;
+                }
+                let left_area: f32 = left_bbox.map_or(0.0, |b| b.surface_area());
+                let cost: f32 = left_area * left_count as f32 + right_areas[b] * right_counts[b] as f32;
+                if best.map_or(true, |(best_cost, _, _)| cost < best_cost) {
+                    best = Some((cost, axis, b));
+                }
+            }
+        }
+
+        let split: Option<(usize, usize)> = match best {
+            Some((cost, axis, bin)) => {
+                let area: f32 = bbox.surface_area();
+                let split_cost: f32 = Self::TRAVERSAL_COST + Self::INTERSECTION_COST * cost / area;
+                let leaf_cost: f32 = Self::INTERSECTION_COST * items.len() as f32;
+                if split_cost < leaf_cost || items.len() > Self::MAX_LEAF_SIZE {
+                    let c_min: f32 = centroid_bbox.min[axis];
+                    let c_max: f32 = centroid_bbox.max[axis];
+                    let mid: usize = partition(items, |item| Self::bin_index(item.centroid[axis], c_min, c_max) < bin);
+                    Some((axis, mid))
+                } else {
+                    None
+                }
+            },
+            None if items.len() > Self::MAX_LEAF_SIZE => {
+                // All centroids are the same point, so split in the middle
+                Some((0, items.len() / 2))
+            },
+            None => None
+        };
+
+        match split {
+            Some((axis, mid)) => {
+                // Push a placeholder and fill it after building children
+                nodes.push(leaf);
+                let (left_items, right_items) = items.split_at_mut(mid);
+                Self::build(nodes, left_items, offset);
+                let right: usize = Self::build(nodes, right_items, offset + mid);
+                nodes[node_idx] = BvhNode::Interior {bbox, right, axis};
+            },
+            None => {
+                nodes.push(leaf);
+            }
+        }
+        node_idx
+    }
+
+    fn bin_index(c: f32, c_min: f32, c_max: f32) -> usize {
+        let b: usize = ((c - c_min) / (c_max - c_min) * Self::N_BINS as f32) as usize;
+        b.min(Self::N_BINS - 1)
+    }
+}
+
+// Move elements satisfying the predicate to the front and return the number of them
+fn partition<T, F: Fn(&T) -> bool>(items: &mut [T], pred: F) -> usize {
+    let mut mid: usize = 0;
+    for i in 0..items.len() {
+        if pred(&items[i]) {
+            items.swap(i, mid);
+            mid += 1;
+        }
+    }
+    mid
+}
+
+impl Bvh {
+    // Find the closest hit by `hit_primitive(index, t_max)`, which hits the primitive of the index
+    pub fn hit<'a, F: FnMut(usize, f32) -> Option<HitRecord<'a>>>(&self, r: &Ray, t_min: f32, t_max: f32, mut hit_primitive: F) -> Option<HitRecord<'a>> {
+        let mut hit_record_opt: Option<HitRecord> = None;
+        let mut closest_so_far: f32 = t_max;
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let mut stack: Vec<usize> = vec![0];
+        while let Some(node_idx) = stack.pop() {
+            let node: &BvhNode = &self.nodes[node_idx];
+            if !node.bbox().hit(r, t_min, closest_so_far) {
+                continue;
+            }
+            match node {
+                BvhNode::Leaf {start, count, ..} => {
+                    for &index in &self.indices[*start..*start + *count] {
+                        if let Some(hit_record) = hit_primitive(index, closest_so_far) {
+                            closest_so_far = hit_record.t;
+                            hit_record_opt = Some(hit_record);
+                        }
+                    }
+                },
+                BvhNode::Interior {right, axis, ..} => {
+                    // Visit the nearer child first
+                    if r.direction[*axis] < 0.0 {
+                        stack.push(node_idx + 1);
+                        stack.push(*right);
+                    } else {
+                        stack.push(*right);
+                        stack.push(node_idx + 1);
+                    }
+                }
+            }
+        }
+        hit_record_opt
+    }
+
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        self.nodes.first().map(|node| *node.bbox())
+    }
+}
+
+impl BvhHitable {
+    pub fn new(hitables: Vec<Box<dyn Hitable + Sync>>) -> BvhHitable {
+        let mut bounded: Vec<Box<dyn Hitable + Sync>> = Vec::new();
+        let mut bboxes: Vec<Aabb> = Vec::new();
+        let mut unbounded: Vec<Box<dyn Hitable + Sync>> = Vec::new();
+        for hitable in hitables {
+            match hitable.bounding_box() {
+                Some(bbox) => {
+                    bboxes.push(bbox);
+                    bounded.push(hitable);
+                },
+                None => unbounded.push(hitable)
+            }
+        }
+        BvhHitable {
+            bvh: Bvh::new(&bboxes),
+            bounded,
+            unbounded: ListHitable {hitables: unbounded}
+        }
+    }
+}
+
+impl Hitable for BvhHitable {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let unbounded_hit: Option<HitRecord> = self.unbounded.hit(r, t_min, t_max);
+        let closest_so_far: f32 = unbounded_hit.as_ref().map_or(t_max, |h| h.t);
+        self.bvh.hit(r, t_min, closest_so_far, |index, t_max| self.bounded[index].hit(r, t_min, t_max))
+            .or(unbounded_hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        if !self.unbounded.hitables.is_empty() {
+            return None;
+        }
+        self.bvh.bounding_box()
+    }
+}
@@ -9,7 +9,10 @@ pub struct Camera {
     pub vfov: f32,
     pub aspect: f32,
     pub aperture: f32,
-    pub focus_dist: f32
+    pub focus_dist: f32,
+    // Shutter open and close times
+    pub time0: f32,
+    pub time1: f32
 }
 
 
@@ -60,9 +63,17 @@ impl Camera {
     pub fn get_ray(&self, rng: &mut rand::rngs::StdRng, s: f32, t: f32) -> Ray {
         let rd    : Vec3 = &random_in_unit_disk(rng) * self.lens_radius();
         let offset: Vec3 = &(&self.u() * rd.x) + &(&self.v() * rd.y);
+        // NOTE: No random number is consumed without motion blur
+        let time: f32 = if self.time1 > self.time0 {
+            self.time0 + rng.gen::<f32>() * (self.time1 - self.time0)
+        } else {
+            self.time0
+        };
         Ray {
             origin: &self.origin() + &offset,
-            direction: &(&(&(&self.lower_left_corner() + &(&self.horizontal() * s)) + &(&self.vertical() * t)) - &self.origin()) - &offset
+            direction: &(&(&(&self.lower_left_corner() + &(&self.horizontal() * s)) + &(&self.vertical() * t)) - &self.origin()) - &offset,
+            time,
+            wavelength: None
         }
     }
 }
@@ -0,0 +1,78 @@
+use std::fs;
+use std::io;
+use std::io::{Read, Write};
+use std::path;
+use serde::{Serialize, Deserialize};
+
+use crate::render::{Accumulation, PixelAccumulator};
+
+// Checkpoint of a render: a JSON header of the settings which determine the image and the accumulation buffer.
+// NOTE: Random numbers are keyed by the sample index of each pixel, so sample counts are the whole state of random generation.
+
+const MAGIC: &[u8; 8] = b"RTIOWCK1";
+
+#[derive(Serialize, Deserialize)]
+pub struct CheckpointHeader {
+    pub width: u32,
+    pub height: u32,
+    pub n_samples: u32,
+    pub min_float: f32,
+    pub random_seed: u64,
+    pub adaptive_threshold: Option<f32>,
+    pub max_samples: Option<u32>,
+    #[serde(default)]
+    pub spectral: bool,
+    pub builtin_scene: String,
+    // Scene description file instead of the built-in scene
+    pub scene: Option<path::PathBuf>,
+    // Output file and its format
+    pub file: path::PathBuf,
+    pub format: String
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// Write the checkpoint atomically through a temporary file so that an interrupted write keeps the previous one
+pub fn write_checkpoint(file_path: &path::Path, header: &CheckpointHeader, accumulation: &Accumulation) -> io::Result<()> {
+    let mut temp_path: std::ffi::OsString = file_path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    {
+        let mut writer = io::BufWriter::new(fs::File::create(&temp_path)?);
+        let header_json: Vec<u8> = serde_json::to_vec(header)?;
+        writer.write_all(MAGIC)?;
+        writer.write_all(&(header_json.len() as u32).to_le_bytes())?;
+        writer.write_all(&header_json)?;
+        for pixel in &accumulation.pixels {
+            pixel.write_le(&mut writer)?;
+        }
+        writer.flush()?;
+    }
+    fs::rename(&temp_path, file_path)
+}
+
+pub fn read_checkpoint(file_path: &path::Path) -> io::Result<(CheckpointHeader, Accumulation)> {
+    let mut reader = io::BufReader::new(fs::File::open(file_path)?);
+    let mut magic: [u8; 8] = [0; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid_data("not a checkpoint file"));
+    }
+    let mut len_bytes: [u8; 4] = [0; 4];
+    reader.read_exact(&mut len_bytes)?;
+    let mut header_json: Vec<u8> = vec![0; u32::from_le_bytes(len_bytes) as usize];
+    reader.read_exact(&mut header_json)?;
+    let header: CheckpointHeader = serde_json::from_slice(&header_json)?;
+
+    let n_pixels: usize = header.width as usize * header.height as usize;
+    let mut data: Vec<u8> = Vec::new();
+    reader.read_to_end(&mut data)?;
+    if data.len() != n_pixels * PixelAccumulator::ENCODED_SIZE {
+        return Err(invalid_data("size of the accumulation buffer does not match the image size"));
+    }
+    let pixels: Vec<PixelAccumulator> = data.chunks(PixelAccumulator::ENCODED_SIZE)
+        .map(|mut chunk| PixelAccumulator::read_le(&mut chunk))
+        .collect::<io::Result<_>>()?;
+    Ok((header, Accumulation {pixels}))
+}
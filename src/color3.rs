@@ -7,8 +7,9 @@ pub struct Color3 {
     pub b: f32
 }
 
+// Linear radiance to an 8-bit integer with gamma 2
 fn color_elem_to_int(f: f32) -> i32 {
-    (255.99f32 * f) as i32
+    (255.99f32 * f.clamp(0.0, 1.0).sqrt()) as i32
 }
 
 impl Color3 {
@@ -33,6 +34,14 @@ impl Add for &Color3 {
     }
 }
 
+impl Mul for &Color3 {
+    type Output = Color3;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Color3{r: self.r * rhs.r, g: self.g * rhs.g, b: self.b * rhs.b}
+    }
+}
+
 impl Mul<f32> for &Color3 {
     type Output = Color3;
 
@@ -0,0 +1,61 @@
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::hitable::Hitable;
+use crate::hitable::HitRecord;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::cylinder_hitable::{azimuth, ClosestHit};
+use core::borrow::Borrow;
+
+// Cone capped by a disk, standing on the Y axis from the center of its base to the apex at the height
+pub struct ConeHitable {
+    pub center: Vec3,
+    pub radius: f32,
+    pub height: f32,
+    pub material: Box<dyn Material + std::marker::Sync>
+}
+
+impl Hitable for ConeHitable {
+    // Surface coordinates of the side are the azimuth and the height by the height,
+    // and the ones of the base are the azimuth and the distance from the axis by the radius
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let o: Vec3 = &r.origin - &self.center;
+        let d: &Vec3 = &r.direction;
+        let mut closest_hit: ClosestHit = ClosestHit::new(t_min, t_max);
+        // x^2 + z^2 = (k (height - y))^2 on the side
+        let k: f32 = self.radius / self.height;
+        let k_squared: f32 = k * k;
+        let h: f32 = self.height - o.y;
+        let a: f32 = d.x * d.x + d.z * d.z - k_squared * d.y * d.y;
+        let b: f32 = o.x * d.x + o.z * d.z + k_squared * h * d.y;
+        let c: f32 = o.x * o.x + o.z * o.z - k_squared * h * h;
+        let discriminant: f32 = b * b - a * c;
+        if a != 0.0 && discriminant > 0.0 {
+            for &t in &[(-b - discriminant.sqrt()) / a, (-b + discriminant.sqrt()) / a] {
+                let p: Vec3 = &o + &(d * t);
+                // NOTE: The other nappe of the double cone is above the apex
+                if 0.0 <= p.y && p.y <= self.height {
+                    let normal: Vec3 = Vec3 {x: p.x, y: k * (p.x * p.x + p.z * p.z).sqrt(), z: p.z}.unit_vector();
+                    closest_hit.add(t, normal, azimuth(p.x, p.z), p.y / self.height);
+                }
+            }
+        }
+        closest_hit.add_cap(&o, d, 0.0, self.radius, -1.0);
+        let (t, normal, u, v) = closest_hit.closest?;
+        Some(HitRecord {
+            t,
+            p: r.point_at_parameter(t),
+            normal,
+            u,
+            v,
+            material: self.material.borrow()
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb {
+            min: &self.center - &Vec3 {x: self.radius, y: 0.0, z: self.radius},
+            max: &self.center + &Vec3 {x: self.radius, y: self.height, z: self.radius}
+        })
+    }
+}
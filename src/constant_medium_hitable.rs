@@ -0,0 +1,62 @@
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::hitable::{Hitable, HitRecord};
+use crate::material::Material;
+use crate::aabb::Aabb;
+use core::borrow::Borrow;
+
+// Medium of constant density such as fog or smoke filling a convex boundary, where rays scatter by the phase function
+pub struct ConstantMediumHitable {
+    pub boundary: Box<dyn Hitable + Sync>,
+    // Probability of scattering per unit distance
+    pub density: f32,
+    pub phase_function: Box<dyn Material + Sync>
+}
+
+fn mix(h: u64) -> u64 {
+    // Finalizer of SplitMix64
+    let h: u64 = (h ^ (h >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    let h: u64 = (h ^ (h >> 27)).wrapping_mul(0x94d049bb133111eb);
+    h ^ (h >> 31)
+}
+
+// Uniform random number in [0, 1) determined by the ray
+// NOTE: Hitables have no random generator, but rays of paths are random enough to decorrelate distances
+fn ray_random(r: &Ray) -> f32 {
+    let values: [f32; 7] = [r.origin.x, r.origin.y, r.origin.z, r.direction.x, r.direction.y, r.direction.z, r.time];
+    let h: u64 = values.iter().fold(0x9e3779b97f4a7c15, |h, value| mix(h ^ value.to_bits() as u64));
+    (h >> 40) as f32 / (1u64 << 24) as f32
+}
+
+impl Hitable for ConstantMediumHitable {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        // Entry and exit of the line of the ray
+        let entry: HitRecord = self.boundary.hit(r, f32::MIN, f32::MAX)?;
+        let exit: HitRecord = self.boundary.hit(r, entry.t + 0.0001, f32::MAX)?;
+        let t1: f32 = entry.t.max(t_min);
+        let t2: f32 = exit.t.min(t_max);
+        if t1 >= t2 {
+            return None;
+        }
+        let length: f32 = r.direction.length();
+        // Exponentially distributed distance to scattering
+        let distance: f32 = -(1.0 - ray_random(r)).ln() / self.density;
+        if distance > (t2 - t1) * length {
+            return None;
+        }
+        let t: f32 = t1 + distance / length;
+        Some(HitRecord {
+            t,
+            p: r.point_at_parameter(t),
+            // NOTE: Phase functions do not depend on normals
+            normal: Vec3 {x: 1.0, y: 0.0, z: 0.0},
+            u: 0.0,
+            v: 0.0,
+            material: self.phase_function.borrow()
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+}
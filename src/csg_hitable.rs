@@ -0,0 +1,100 @@
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::hitable::{Hitable, HitRecord, Interval};
+use crate::aabb::Aabb;
+
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    // Left minus right
+    Difference
+}
+
+impl CsgOperation {
+    fn inside(&self, inside_left: bool, inside_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => inside_left || inside_right,
+            CsgOperation::Intersection => inside_left && inside_right,
+            CsgOperation::Difference => inside_left && !inside_right
+        }
+    }
+}
+
+// Constructive solid geometry combining two closed hitables by their intervals along rays,
+// where each surface bounding the result keeps its own material
+pub struct CsgHitable {
+    pub left: Box<dyn Hitable + Sync>,
+    pub right: Box<dyn Hitable + Sync>,
+    pub operation: CsgOperation
+}
+
+// Normal oriented against the ray at an entry and along it at an exit
+// (surfaces of the right hitable bounding a difference are turned inside out)
+fn oriented<'a>(r: &Ray, hit_record: HitRecord<'a>, entry: bool) -> HitRecord<'a> {
+    if (r.direction.dot(&hit_record.normal) < 0.0) == entry {
+        hit_record
+    } else {
+        HitRecord {normal: -&hit_record.normal, ..hit_record}
+    }
+}
+
+impl Hitable for CsgHitable {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        self.intervals(r).into_iter()
+            .flat_map(|interval| vec![interval.entry, interval.exit])
+            .find(|hit_record| t_min < hit_record.t && hit_record.t < t_max)
+    }
+
+    // Intervals of both hitables are swept in the order of their boundaries
+    fn intervals(&self, r: &Ray) -> Vec<Interval<'_>> {
+        // Boundaries with whether they belong to the left hitable and whether they are entries
+        let mut boundaries: Vec<(HitRecord, bool, bool)> = Vec::new();
+        for (hitable, is_left) in &[(&self.left, true), (&self.right, false)] {
+            for interval in hitable.intervals(r) {
+                boundaries.push((interval.entry, *is_left, true));
+                boundaries.push((interval.exit, *is_left, false));
+            }
+        }
+        boundaries.sort_by(|(a, _, _), (b, _, _)| a.t.partial_cmp(&b.t).unwrap());
+
+        let mut intervals: Vec<Interval> = Vec::new();
+        let mut inside_left: bool = false;
+        let mut inside_right: bool = false;
+        let mut entry_opt: Option<HitRecord> = None;
+        for (hit_record, is_left, is_entry) in boundaries {
+            let was_inside: bool = self.operation.inside(inside_left, inside_right);
+            if is_left {
+                inside_left = is_entry;
+            } else {
+                inside_right = is_entry;
+            }
+            match (was_inside, self.operation.inside(inside_left, inside_right)) {
+                (false, true) => entry_opt = Some(oriented(r, hit_record, true)),
+                (true, false) => {
+                    if let Some(entry) = entry_opt.take() {
+                        intervals.push(Interval {entry, exit: oriented(r, hit_record, false)});
+                    }
+                },
+                _ => {}
+            }
+        }
+        intervals
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        match self.operation {
+            CsgOperation::Union => Some(self.left.bounding_box()?.surrounding_box(&self.right.bounding_box()?)),
+            CsgOperation::Intersection => {
+                // Overlap of the boxes, or the box of either one if the other is unbounded
+                match (self.left.bounding_box(), self.right.bounding_box()) {
+                    (Some(a), Some(b)) => Some(Aabb {
+                        min: Vec3 {x: a.min.x.max(b.min.x), y: a.min.y.max(b.min.y), z: a.min.z.max(b.min.z)},
+                        max: Vec3 {x: a.max.x.min(b.max.x), y: a.max.y.min(b.max.y), z: a.max.z.min(b.max.z)}
+                    }),
+                    (a, b) => a.or(b)
+                }
+            },
+            CsgOperation::Difference => self.left.bounding_box()
+        }
+    }
+}
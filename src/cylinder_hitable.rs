@@ -0,0 +1,92 @@
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::hitable::Hitable;
+use crate::hitable::HitRecord;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use core::borrow::Borrow;
+
+// Angle around the Y axis from X=-1 (as in sphere_uv) in [0, 1]
+pub fn azimuth(x: f32, z: f32) -> f32 {
+    ((-z).atan2(x) + std::f32::consts::PI) / (2.0 * std::f32::consts::PI)
+}
+
+// Closest hit among candidates of t, normal and surface coordinates
+pub struct ClosestHit {
+    t_min: f32,
+    pub closest: Option<(f32, Vec3, f32, f32)>,
+    t_max: f32
+}
+
+impl ClosestHit {
+    pub fn new(t_min: f32, t_max: f32) -> ClosestHit {
+        ClosestHit {t_min, closest: None, t_max}
+    }
+
+    pub fn add(&mut self, t: f32, normal: Vec3, u: f32, v: f32) {
+        if self.t_min < t && t < self.t_max {
+            self.t_max = t;
+            self.closest = Some((t, normal, u, v));
+        }
+    }
+
+    // Disk cap of the radius at the height y of the local ray (origin relative to the center of the shape)
+    pub fn add_cap(&mut self, o: &Vec3, d: &Vec3, y: f32, radius: f32, normal_y: f32) {
+        let t: f32 = (y - o.y) / d.y;
+        let x: f32 = o.x + t * d.x;
+        let z: f32 = o.z + t * d.z;
+        let distance_squared: f32 = x * x + z * z;
+        if distance_squared <= radius * radius {
+            self.add(t, Vec3 {x: 0.0, y: normal_y, z: 0.0}, azimuth(x, z), distance_squared.sqrt() / radius);
+        }
+    }
+}
+
+// Cylinder capped by disks, standing on the Y axis from the center of its bottom
+pub struct CylinderHitable {
+    pub center: Vec3,
+    pub radius: f32,
+    pub height: f32,
+    pub material: Box<dyn Material + std::marker::Sync>
+}
+
+impl Hitable for CylinderHitable {
+    // Surface coordinates of the side are the azimuth and the height by the height,
+    // and the ones of caps are the azimuth and the distance from the axis by the radius
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let o: Vec3 = &r.origin - &self.center;
+        let d: &Vec3 = &r.direction;
+        let mut closest_hit: ClosestHit = ClosestHit::new(t_min, t_max);
+        let a: f32 = d.x * d.x + d.z * d.z;
+        let b: f32 = o.x * d.x + o.z * d.z;
+        let c: f32 = o.x * o.x + o.z * o.z - self.radius * self.radius;
+        let discriminant: f32 = b * b - a * c;
+        if a > 0.0 && discriminant > 0.0 {
+            for &t in &[(-b - discriminant.sqrt()) / a, (-b + discriminant.sqrt()) / a] {
+                let p: Vec3 = &o + &(d * t);
+                if 0.0 <= p.y && p.y <= self.height {
+                    let normal: Vec3 = Vec3 {x: p.x / self.radius, y: 0.0, z: p.z / self.radius};
+                    closest_hit.add(t, normal, azimuth(p.x, p.z), p.y / self.height);
+                }
+            }
+        }
+        closest_hit.add_cap(&o, d, 0.0, self.radius, -1.0);
+        closest_hit.add_cap(&o, d, self.height, self.radius, 1.0);
+        let (t, normal, u, v) = closest_hit.closest?;
+        Some(HitRecord {
+            t,
+            p: r.point_at_parameter(t),
+            normal,
+            u,
+            v,
+            material: self.material.borrow()
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb {
+            min: &self.center - &Vec3 {x: self.radius, y: 0.0, z: self.radius},
+            max: &self.center + &Vec3 {x: self.radius, y: self.height, z: self.radius}
+        })
+    }
+}
@@ -0,0 +1,80 @@
+use rand::prelude::*;
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::hitable::Hitable;
+use crate::hitable::HitRecord;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::onb::Onb;
+use crate::plane_hitable::hit_plane;
+use core::borrow::Borrow;
+
+// Disk facing the normal
+pub struct DiskHitable {
+    pub center: Vec3,
+    pub normal: Vec3,
+    pub radius: f32,
+    pub material: Box<dyn Material + std::marker::Sync>
+}
+
+impl DiskHitable {
+    // t and surface coordinates (u: angle around the normal, v: distance from the center by the radius)
+    fn hit_disk(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<(f32, f32, f32)> {
+        let onb: Onb = Onb::from_w(&self.normal);
+        let t: f32 = hit_plane(r, &self.center, &onb.w, t_min, t_max)?;
+        let d: Vec3 = &r.point_at_parameter(t) - &self.center;
+        let distance: f32 = d.length();
+        if distance > self.radius {
+            return None;
+        }
+        let phi: f32 = d.dot(&onb.v).atan2(d.dot(&onb.u)) + std::f32::consts::PI;
+        Some((t, phi / (2.0 * std::f32::consts::PI), distance / self.radius))
+    }
+}
+
+impl Hitable for DiskHitable {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let (t, u, v) = self.hit_disk(r, t_min, t_max)?;
+        Some(HitRecord {
+            t,
+            p: r.point_at_parameter(t),
+            normal: self.normal.unit_vector(),
+            u,
+            v,
+            material: self.material.borrow()
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let n: Vec3 = self.normal.unit_vector();
+        // NOTE: Padded so that axis-aligned disks do not have zero-thickness boxes
+        let extent = |n: f32| self.radius * (1.0 - n * n).max(0.0).sqrt() + 1e-4;
+        let e: Vec3 = Vec3 {x: extent(n.x), y: extent(n.y), z: extent(n.z)};
+        Some(Aabb {
+            min: &self.center - &e,
+            max: &self.center + &e
+        })
+    }
+
+    // Points are sampled uniformly on the disk, converted into the density per solid angle
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        let r: Ray = Ray {origin: *origin, direction: *direction, time: 0.0, wavelength: None};
+        match self.hit_disk(&r, 0.0, f32::MAX) {
+            Some((t, _, _)) => {
+                let area: f32 = std::f32::consts::PI * self.radius * self.radius;
+                let dist_squared: f32 = t * t * direction.squared_length();
+                let cosine: f32 = (self.normal.unit_vector().dot(direction) / direction.length()).abs();
+                dist_squared / (cosine * area)
+            },
+            None => 0.0
+        }
+    }
+
+    fn random(&self, rng: &mut rand::rngs::StdRng, origin: &Vec3) -> Vec3 {
+        let onb: Onb = Onb::from_w(&self.normal);
+        let radius: f32 = self.radius * rng.gen::<f32>().sqrt();
+        let phi: f32 = 2.0 * std::f32::consts::PI * rng.gen::<f32>();
+        let p: Vec3 = &self.center + &onb.local(&Vec3 {x: radius * phi.cos(), y: radius * phi.sin(), z: 0.0});
+        &p - origin
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::io;
+use std::io::{BufRead, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::path;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+
+use crate::render::{self, RenderSettings, Tile, PixelAccumulator, Accumulation};
+use crate::scene::Scene;
+use crate::random_scenes;
+use crate::scene_file;
+use crate::util;
+use crate::BuiltinScene;
+
+// Distributed rendering: a coordinator hands out tiles of frames to workers connected over TCP and assembles the frames.
+// Messages are JSON lines, and a worker answers a tile with its pixels in the binary encoding of PixelAccumulator.
+// NOTE: Random numbers only depend on the seed, the frame, the pixel and the sample, so the image is the same as a local render.
+
+// Scene which workers build by themselves
+// NOTE: Files of a scene description are read by workers from the same paths
+#[derive(Clone, Serialize, Deserialize)]
+pub enum SceneSpec {
+    Builtin {name: String},
+    File {path: path::PathBuf},
+    FreeFallAnimation {dt: f32, min_t: f32, max_t: f32, shutter: f32, skip_step: usize}
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub settings: RenderSettings,
+    pub scene: SceneSpec
+}
+
+#[derive(Serialize, Deserialize)]
+enum Message {
+    Job(Job),
+    Tile {frame: u64, tile: Tile},
+    Done
+}
+
+#[derive(Clone, Copy)]
+struct WorkUnit {
+    frame: u64,
+    tile: Tile
+}
+
+fn write_message(stream: &mut TcpStream, message: &Message) -> io::Result<()> {
+    let mut line: Vec<u8> = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    stream.write_all(&line)
+}
+
+// Read a message, or None at the end of the stream
+fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<Message>> {
+    let mut line: String = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&line)?))
+}
+
+// Scenes of frames built by a worker, advancing the animation to the requested frame
+struct FrameScenes<'a> {
+    job: &'a Job,
+    scenes: Box<dyn Iterator<Item=Scene>>,
+    // Frame of the next scene of the iterator
+    next_frame: u64,
+    current: Option<(u64, Scene)>
+}
+
+impl <'a> FrameScenes<'a> {
+    fn new(job: &'a Job) -> Result<FrameScenes<'a>, String> {
+        let scenes: Box<dyn Iterator<Item=Scene>> = FrameScenes::scenes(job)?;
+        Ok(FrameScenes {job, scenes, next_frame: 0, current: None})
+    }
+
+    fn scenes(job: &Job) -> Result<Box<dyn Iterator<Item=Scene>>, String> {
+        let settings: &RenderSettings = &job.settings;
+        let scenes: Box<dyn Iterator<Item=Scene>> = match &job.scene {
+            SceneSpec::Builtin {name} => {
+                let builtin_scene: BuiltinScene = name.parse()?;
+                let mut rng: rand::rngs::StdRng = util::rng_by_seed(settings.random_seed);
+                Box::new(std::iter::once(builtin_scene.scene(&mut rng, settings.width, settings.height)))
+            },
+            SceneSpec::File {path} => {
+                let scene: Scene = scene_file::load(path, settings.width, settings.height)
+                    .map_err(|err| format!("{}: {}", path.display(), err))?;
+                Box::new(std::iter::once(scene))
+            },
+            &SceneSpec::FreeFallAnimation {dt, min_t, max_t, shutter, skip_step} => {
+                let animation = random_scenes::FreeFallAnimation::new(settings.width, settings.height, dt, min_t, max_t, shutter, settings.random_seed);
+                Box::new(util::skip_by_step(animation, skip_step))
+            }
+        };
+        Ok(scenes)
+    }
+
+    fn scene(&mut self, frame: u64) -> Result<&Scene, String> {
+        if self.current.as_ref().map(|(current_frame, _)| *current_frame) != Some(frame) {
+            // Start over for a past frame
+            if frame < self.next_frame {
+                self.scenes = FrameScenes::scenes(self.job)?;
+                self.next_frame = 0;
+            }
+            let scene: Scene = self.scenes.nth((frame - self.next_frame) as usize)
+                .ok_or_else(|| format!("no frame {}", frame))?;
+            self.next_frame = frame + 1;
+            self.current = Some((frame, scene));
+        }
+        Ok(&self.current.as_ref().unwrap().1)
+    }
+}
+
+// Work for the coordinator at the address until it has no more tiles
+pub fn work<A: ToSocketAddrs>(coordinator_addr: A) -> Result<(), String> {
+    let mut stream: TcpStream = TcpStream::connect(coordinator_addr).map_err(|err| err.to_string())?;
+    let mut reader = io::BufReader::new(stream.try_clone().map_err(|err| err.to_string())?);
+    let job: Job = match read_message(&mut reader).map_err(|err| err.to_string())? {
+        Some(Message::Job(job)) => job,
+        _ => return Err("expected a job from the coordinator".to_string())
+    };
+    let mut frame_scenes: FrameScenes = FrameScenes::new(&job)?;
+    loop {
+        // NOTE: The coordinator may close the connection without Done when it finishes
+        match read_message(&mut reader).map_err(|err| err.to_string())? {
+            Some(Message::Tile {frame, tile}) => {
+                let scene: &Scene = frame_scenes.scene(frame)?;
+                let pixels: Vec<PixelAccumulator> = render::render_tile(scene, &job.settings, frame, &tile);
+                let mut data: Vec<u8> = Vec::with_capacity(pixels.len() * PixelAccumulator::ENCODED_SIZE);
+                for pixel in &pixels {
+                    pixel.write_le(&mut data).map_err(|err| err.to_string())?;
+                }
+                stream.write_all(&data).map_err(|err| err.to_string())?;
+            },
+            Some(Message::Done) | None => return Ok(()),
+            Some(Message::Job(_)) => return Err("unexpected job from the coordinator".to_string())
+        }
+    }
+}
+
+// Send tiles to the worker and collect its results until no tile is left,
+// putting the tile back into the queue if the worker drops out or does not answer within the timeout
+// NOTE: The queue gives None when all frames are finished, which is passed on to the other workers
+fn serve_worker(mut stream: TcpStream, job_line: &[u8], timeout: Duration, units: &Mutex<mpsc::Receiver<Option<WorkUnit>>>, queue: mpsc::Sender<Option<WorkUnit>>, results: mpsc::Sender<(WorkUnit, Vec<PixelAccumulator>)>) -> io::Result<()> {
+    // NOTE: A worker disappearing silently (power loss or a network partition) is only noticed by timeouts
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    let mut reader = io::BufReader::new(stream.try_clone()?);
+    stream.write_all(job_line)?;
+    loop {
+        // Wait for a tile including ones put back by workers dropping out
+        let unit: Option<WorkUnit> = units.lock().unwrap().recv().unwrap_or(None);
+        let unit: WorkUnit = match unit {
+            Some(unit) => unit,
+            None => {
+                let _ = queue.send(None);
+                return write_message(&mut stream, &Message::Done);
+            }
+        };
+        let result: io::Result<Vec<PixelAccumulator>> = write_message(&mut stream, &Message::Tile {frame: unit.frame, tile: unit.tile})
+            .and_then(|_| (0..unit.tile.width * unit.tile.height).map(|_| PixelAccumulator::read_le(&mut reader)).collect());
+        match result {
+            Ok(pixels) => {
+                if results.send((unit, pixels)).is_err() {
+                    return Ok(());
+                }
+            },
+            Err(err) => {
+                let _ = queue.send(Some(unit));
+                return Err(match err.kind() {
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => io::Error::new(io::ErrorKind::TimedOut, "no answer within the timeout"),
+                    _ => err
+                });
+            }
+        }
+    }
+}
+
+// Render the frames by workers connecting to the address, calling `on_frame` with each frame when all its tiles are rendered
+// NOTE: A tile which a worker does not finish within the timeout is given to another worker
+pub fn coordinate<A: ToSocketAddrs, F: FnMut(u64, Accumulation)>(addr: A, job: &Job, frames: &[u64], timeout: Duration, mut on_frame: F) -> io::Result<()> {
+    let settings: &RenderSettings = &job.settings;
+    let tiles: Vec<Tile> = render::tiles(settings.width, settings.height, settings.tile_size);
+    let (unit_sender, unit_receiver) = mpsc::channel::<Option<WorkUnit>>();
+    for &frame in frames {
+        for &tile in &tiles {
+            let _ = unit_sender.send(Some(WorkUnit {frame, tile}));
+        }
+    }
+    let units: Arc<Mutex<mpsc::Receiver<Option<WorkUnit>>>> = Arc::new(Mutex::new(unit_receiver));
+    let (result_sender, result_receiver) = mpsc::channel::<(WorkUnit, Vec<PixelAccumulator>)>();
+
+    let listener: TcpListener = TcpListener::bind(addr)?;
+    eprintln!("Waiting for workers on {}", listener.local_addr()?);
+    let mut job_line: Vec<u8> = serde_json::to_vec(&Message::Job(job.clone()))?;
+    job_line.push(b'\n');
+    {
+        let unit_sender = unit_sender.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream: TcpStream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue
+                };
+                let peer: String = stream.peer_addr().map(|addr| addr.to_string()).unwrap_or_default();
+                eprintln!("Worker {} connected", peer);
+                let job_line = job_line.clone();
+                let units = units.clone();
+                let unit_sender = unit_sender.clone();
+                let result_sender = result_sender.clone();
+                thread::spawn(move || {
+                    if let Err(err) = serve_worker(stream, &job_line, timeout, &units, unit_sender, result_sender) {
+                        eprintln!("Worker {} dropped out: {}", peer, err);
+                    }
+                });
+            }
+        });
+    }
+
+    // Assemble tiles into frames
+    let mut frame_accumulations: HashMap<u64, (Accumulation, usize)> = HashMap::new();
+    let mut n_remaining_frames: usize = frames.len();
+    while n_remaining_frames > 0 {
+        let (unit, pixels) = result_receiver.recv().unwrap();
+        let (accumulation, n_tiles) = frame_accumulations.entry(unit.frame)
+            .or_insert_with(|| (Accumulation::new(settings), 0));
+        for (pixel_idx, pixel) in unit.tile.pixel_indices(settings.width).zip(pixels) {
+            accumulation.pixels[pixel_idx] = pixel;
+        }
+        *n_tiles += 1;
+        if *n_tiles == tiles.len() {
+            let (accumulation, _) = frame_accumulations.remove(&unit.frame).unwrap();
+            on_frame(unit.frame, accumulation);
+            n_remaining_frames -= 1;
+        }
+    }
+    let _ = unit_sender.send(None);
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use std::io;
+use std::io::Write;
+
+use crate::color3::Color3;
+
+// OpenEXR encoder for uncompressed single-part scanline images with 32-bit float RGB channels
+
+fn write_attribute<W: Write>(writer: &mut W, name: &str, attribute_type: &str, value: &[u8]) -> io::Result<()> {
+    writer.write_all(name.as_bytes())?;
+    writer.write_all(&[0])?;
+    writer.write_all(attribute_type.as_bytes())?;
+    writer.write_all(&[0])?;
+    writer.write_all(&(value.len() as i32).to_le_bytes())?;
+    writer.write_all(value)
+}
+
+fn header(width: u32, height: u32) -> io::Result<Vec<u8>> {
+    let mut h: Vec<u8> = Vec::new();
+    // Magic number and version 2 (single-part scanline)
+    h.write_all(&[0x76, 0x2f, 0x31, 0x01])?;
+    h.write_all(&2i32.to_le_bytes())?;
+
+    // Channels must be sorted by name
+    let mut channels: Vec<u8> = Vec::new();
+    for name in &["B", "G", "R"] {
+        channels.write_all(name.as_bytes())?;
+        channels.write_all(&[0])?;
+        // pixel type FLOAT, pLinear and reserved
+        channels.write_all(&2i32.to_le_bytes())?;
+        channels.write_all(&[0, 0, 0, 0])?;
+        // x and y sampling
+        channels.write_all(&1i32.to_le_bytes())?;
+        channels.write_all(&1i32.to_le_bytes())?;
+    }
+    channels.write_all(&[0])?;
+    write_attribute(&mut h, "channels", "chlist", &channels)?;
+
+    // NO_COMPRESSION
+    write_attribute(&mut h, "compression", "compression", &[0])?;
+    let mut window: Vec<u8> = Vec::new();
+    for v in &[0, 0, width as i32 - 1, height as i32 - 1] {
+        window.write_all(&v.to_le_bytes())?;
+    }
+    write_attribute(&mut h, "dataWindow", "box2i", &window)?;
+    write_attribute(&mut h, "displayWindow", "box2i", &window)?;
+    // INCREASING_Y
+    write_attribute(&mut h, "lineOrder", "lineOrder", &[0])?;
+    write_attribute(&mut h, "pixelAspectRatio", "float", &1.0f32.to_le_bytes())?;
+    let mut center: Vec<u8> = Vec::new();
+    center.write_all(&0.0f32.to_le_bytes())?;
+    center.write_all(&0.0f32.to_le_bytes())?;
+    write_attribute(&mut h, "screenWindowCenter", "v2f", &center)?;
+    write_attribute(&mut h, "screenWindowWidth", "float", &1.0f32.to_le_bytes())?;
+    // End of header
+    h.write_all(&[0])?;
+    Ok(h)
+}
+
+// Write linear colors (top row first)
+pub fn write_exr<W: Write>(writer: &mut W, width: u32, height: u32, colors: &[Color3]) -> io::Result<()> {
+    let header: Vec<u8> = header(width, height)?;
+    writer.write_all(&header)?;
+
+    // Offset table (one scanline per block)
+    let line_data_size: u64 = width as u64 * 3 * 4;
+    let block_size: u64 = 4 + 4 + line_data_size;
+    let first_block: u64 = header.len() as u64 + height as u64 * 8;
+    for y in 0..height as u64 {
+        writer.write_all(&(first_block + y * block_size).to_le_bytes())?;
+    }
+
+    for (y, row) in colors.chunks(width.max(1) as usize).enumerate() {
+        writer.write_all(&(y as i32).to_le_bytes())?;
+        writer.write_all(&(line_data_size as i32).to_le_bytes())?;
+        let channels: [fn(&Color3) -> f32; 3] = [|c| c.b, |c| c.g, |c| c.r];
+        for channel in &channels {
+            for col in row {
+                writer.write_all(&channel(col).to_le_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_u32(bytes: &[u8], pos: usize) -> u32 {
+        u32::from_le_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]])
+    }
+
+    // Attribute names of the header and the position after it
+    fn read_header(bytes: &[u8]) -> (Vec<String>, usize) {
+        let mut names: Vec<String> = Vec::new();
+        let mut pos: usize = 8;
+        while bytes[pos] != 0 {
+            let name_end: usize = pos + bytes[pos..].iter().position(|&b| b == 0).unwrap();
+            names.push(String::from_utf8(bytes[pos..name_end].to_vec()).unwrap());
+            let type_end: usize = name_end + 1 + bytes[name_end + 1..].iter().position(|&b| b == 0).unwrap();
+            pos = type_end + 5 + read_u32(bytes, type_end + 1) as usize;
+        }
+        (names, pos + 1)
+    }
+
+    #[test]
+    fn scanline_blocks_hold_the_colors() {
+        let (width, height): (u32, u32) = (3, 2);
+        let colors: Vec<Color3> = (0..width * height)
+            .map(|i| Color3 {r: i as f32, g: i as f32 * 0.5, b: -(i as f32)})
+            .collect();
+        let mut bytes: Vec<u8> = Vec::new();
+        write_exr(&mut bytes, width, height, &colors).unwrap();
+
+        assert_eq!(&bytes[..8], &[0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0]);
+        let (names, header_end) = read_header(&bytes);
+        for name in &["channels", "compression", "dataWindow", "displayWindow", "lineOrder",
+                      "pixelAspectRatio", "screenWindowCenter", "screenWindowWidth"] {
+            assert!(names.iter().any(|n| n == name), "missing attribute {}", name);
+        }
+
+        let block_size: usize = 8 + width as usize * 3 * 4;
+        assert_eq!(bytes.len(), header_end + height as usize * (8 + block_size));
+        for y in 0..height as usize {
+            let offset: usize = read_u32(&bytes, header_end + y * 8) as usize;
+            assert_eq!(offset, header_end + height as usize * 8 + y * block_size);
+            assert_eq!(read_u32(&bytes, offset) as usize, y);
+            assert_eq!(read_u32(&bytes, offset + 4) as usize, block_size - 8);
+            // Channels in the order B, G, R, each one a whole scanline
+            for x in 0..width as usize {
+                let c: &Color3 = &colors[y * width as usize + x];
+                let sample = |channel: usize| {
+                    f32::from_bits(read_u32(&bytes, offset + 8 + (channel * width as usize + x) * 4))
+                };
+                assert_eq!((sample(0), sample(1), sample(2)), (c.b, c.g, c.r));
+            }
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::hitable::Hitable;
+use crate::hitable::HitRecord;
+use crate::aabb::Aabb;
+
+// Hitable with normals flipped, such as walls of a room facing inwards
+pub struct FlipNormalsHitable {
+    pub hitable: Box<dyn Hitable + std::marker::Sync>
+}
+
+impl Hitable for FlipNormalsHitable {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let hit_record: HitRecord = self.hitable.hit(r, t_min, t_max)?;
+        Some(HitRecord {normal: -&hit_record.normal, ..hit_record})
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.hitable.bounding_box()
+    }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        self.hitable.pdf_value(origin, direction)
+    }
+
+    fn random(&self, rng: &mut rand::rngs::StdRng, origin: &Vec3) -> Vec3 {
+        self.hitable.random(rng, origin)
+    }
+}
@@ -1,14 +1,76 @@
+use std::sync::Arc;
 use crate::vec3::Vec3;
 use crate::ray::Ray;
 use crate::material::Material;
+use crate::aabb::Aabb;
 
 pub struct HitRecord<'a> {
     pub t: f32,
     pub p: Vec3,
     pub normal: Vec3,
-    pub material: &'a (Material + std::marker::Sync)
+    // Surface coordinates
+    pub u: f32,
+    pub v: f32,
+    pub material: &'a (dyn Material + std::marker::Sync)
 }
 
-pub trait Hitable {
-    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord>;
+// Part of the line of a ray inside a closed hitable, from the entry to the exit
+pub struct Interval<'a> {
+    pub entry: HitRecord<'a>,
+    pub exit: HitRecord<'a>
+}
+
+// NOTE: Send (with materials and textures) so that instances can share a hitable by Arc
+pub trait Hitable: Send {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>>;
+    // Intervals of the whole line of the ray inside the hitable sorted by t, for closed hitables with outward normals
+    // NOTE: By default, hits are found one after another, telling entries from exits by the normals
+    fn intervals(&self, r: &Ray) -> Vec<Interval<'_>> {
+        let mut intervals: Vec<Interval> = Vec::new();
+        let mut entry_opt: Option<HitRecord> = None;
+        let mut t_min: f32 = f32::MIN;
+        while let Some(hit_record) = self.hit(r, t_min, f32::MAX) {
+            t_min = hit_record.t + 0.0001;
+            if r.direction.dot(&hit_record.normal) < 0.0 {
+                entry_opt = Some(hit_record);
+            } else if let Some(entry) = entry_opt.take() {
+                intervals.push(Interval {entry, exit: hit_record});
+            }
+        }
+        intervals
+    }
+    // Bounding box (None if the hitable is unbounded)
+    fn bounding_box(&self) -> Option<Aabb>;
+    // Density (per solid angle) of `random` choosing the direction from the origin,
+    // implemented by hitables which can be sampled as lights
+    fn pdf_value(&self, _origin: &Vec3, _direction: &Vec3) -> f32 {
+        0.0
+    }
+    // Random direction from the origin towards the hitable
+    fn random(&self, _rng: &mut rand::rngs::StdRng, _origin: &Vec3) -> Vec3 {
+        Vec3 {x: 1.0, y: 0.0, z: 0.0}
+    }
+}
+
+// Hitable shared by Arc, such as an emissive object which is also in the lights of the scene
+impl<H: Hitable + Sync + ?Sized> Hitable for Arc<H> {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        (**self).hit(r, t_min, t_max)
+    }
+
+    fn intervals(&self, r: &Ray) -> Vec<Interval<'_>> {
+        (**self).intervals(r)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        (**self).bounding_box()
+    }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        (**self).pdf_value(origin, direction)
+    }
+
+    fn random(&self, rng: &mut rand::rngs::StdRng, origin: &Vec3) -> Vec3 {
+        (**self).random(rng, origin)
+    }
 }
@@ -0,0 +1,205 @@
+use std::io;
+use std::io::{Read, Write};
+use std::fs;
+use std::path;
+
+use crate::color3::Color3;
+use crate::png;
+use crate::radiance_hdr;
+use crate::exr;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ImageFormat {
+    // ASCII PPM (P3)
+    Ppm,
+    Png,
+    // Portable float map (linear)
+    Pfm,
+    // Radiance RGBE (linear)
+    Hdr,
+    // OpenEXR (linear)
+    Exr
+}
+
+impl std::str::FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ppm" => Ok(ImageFormat::Ppm),
+            "png" => Ok(ImageFormat::Png),
+            "pfm" => Ok(ImageFormat::Pfm),
+            "hdr" => Ok(ImageFormat::Hdr),
+            "exr" => Ok(ImageFormat::Exr),
+            _ => Err(format!("unknown format: {} (available: ppm, png, pfm, hdr, exr)", s))
+        }
+    }
+}
+
+impl ImageFormat {
+    // Guess a format from the extension of a file path
+    pub fn from_path(file_path: &path::Path) -> Option<ImageFormat> {
+        file_path.extension()?.to_str()?.parse().ok()
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Png => "png",
+            ImageFormat::Pfm => "pfm",
+            ImageFormat::Hdr => "hdr",
+            ImageFormat::Exr => "exr"
+        }
+    }
+}
+
+// Write linear colors (top row first) in the format
+// NOTE: 8-bit formats are gamma-corrected and clamped while float formats keep linear radiance
+pub fn write_image<W: Write>(writer: &mut W, format: ImageFormat, width: u32, height: u32, colors: &[Color3]) -> io::Result<()> {
+    match format {
+        ImageFormat::Ppm => {
+            writer.write_all(format!("P3\n{} {}\n255\n", width, height).as_bytes())?;
+            for col in colors {
+                writer.write_all(format!("{} {} {}\n", col.ir(), col.ig(), col.ib()).as_bytes())?;
+            }
+            Ok(())
+        },
+        ImageFormat::Png => {
+            let rgb: Vec<u8> = colors.iter()
+                .flat_map(|col| vec![col.ir() as u8, col.ig() as u8, col.ib() as u8])
+                .collect();
+            png::write_png(writer, width, height, &rgb)
+        },
+        ImageFormat::Pfm => {
+            // Negative scale means little endian
+            writer.write_all(format!("PF\n{} {}\n-1.0\n", width, height).as_bytes())?;
+            // NOTE: Rows are stored from bottom to top
+            for row in colors.chunks(width.max(1) as usize).rev() {
+                for col in row {
+                    writer.write_all(&col.r.to_le_bytes())?;
+                    writer.write_all(&col.g.to_le_bytes())?;
+                    writer.write_all(&col.b.to_le_bytes())?;
+                }
+            }
+            Ok(())
+        },
+        ImageFormat::Hdr => radiance_hdr::write_hdr(writer, width, height, colors),
+        ImageFormat::Exr => exr::write_exr(writer, width, height, colors)
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// Read whitespace separated header tokens skipping comments, and the position after the last token
+fn read_header_tokens(bytes: &[u8], n: usize, format_name: &str) -> io::Result<(Vec<String>, usize)> {
+    let mut pos: usize = 0;
+    let mut tokens: Vec<String> = Vec::new();
+    while tokens.len() < n {
+        while pos < bytes.len() && (bytes[pos].is_ascii_whitespace() || bytes[pos] == b'#') {
+            if bytes[pos] == b'#' {
+                while pos < bytes.len() && bytes[pos] != b'\n' {
+                    pos += 1;
+                }
+            } else {
+                pos += 1;
+            }
+        }
+        let start: usize = pos;
+        while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        if start == pos {
+            return Err(invalid_data(format!("unexpected end of {} header", format_name)));
+        }
+        tokens.push(String::from_utf8_lossy(&bytes[start..pos]).to_string());
+    }
+    Ok((tokens, pos))
+}
+
+// Read a PPM (P3 or P6) image as RGB samples (top row first) and the max value
+fn read_ppm(bytes: &[u8]) -> io::Result<(u32, u32, Vec<u8>)> {
+    let (tokens, pos) = read_header_tokens(bytes, 4, "PPM")?;
+    let parse = |s: &str| s.parse::<u32>().map_err(|_| invalid_data(format!("invalid number in PPM header: {}", s)));
+    let (width, height, max_value) = (parse(&tokens[1])?, parse(&tokens[2])?, parse(&tokens[3])?);
+    if max_value == 0 || max_value > 255 {
+        return Err(invalid_data(format!("unsupported PPM max value: {}", max_value)));
+    }
+    let n: usize = width as usize * height as usize * 3;
+    let to_8bit = |v: u32| (v * 255 / max_value) as u8;
+    let samples: Vec<u8> = match tokens[0].as_str() {
+        "P3" => {
+            let values: Vec<u32> = String::from_utf8_lossy(&bytes[pos..])
+                .split_ascii_whitespace()
+                .take(n)
+                .map(parse)
+                .collect::<io::Result<Vec<u32>>>()?;
+            values.into_iter().map(to_8bit).collect()
+        },
+        // NOTE: A single whitespace separates the header and the binary data
+        "P6" => bytes.get(pos + 1..pos + 1 + n)
+            .ok_or_else(|| invalid_data("PPM data is too short".to_string()))?
+            .iter()
+            .map(|&v| to_8bit(v as u32))
+            .collect(),
+        magic => return Err(invalid_data(format!("unsupported PPM type: {}", magic)))
+    };
+    if samples.len() < n {
+        return Err(invalid_data("PPM data is too short".to_string()));
+    }
+    Ok((width, height, samples))
+}
+
+// Read a portable float map (PF for RGB or Pf for grayscale) as linear colors (top row first)
+fn read_pfm(bytes: &[u8]) -> io::Result<(u32, u32, Vec<Color3>)> {
+    let (tokens, pos) = read_header_tokens(bytes, 4, "PFM")?;
+    let channels: usize = match tokens[0].as_str() {
+        "PF" => 3,
+        "Pf" => 1,
+        magic => return Err(invalid_data(format!("unsupported PFM type: {}", magic)))
+    };
+    let parse = |s: &str| s.parse::<u32>().map_err(|_| invalid_data(format!("invalid number in PFM header: {}", s)));
+    let (width, height) = (parse(&tokens[1])?, parse(&tokens[2])?);
+    let scale: f32 = tokens[3].parse().map_err(|_| invalid_data(format!("invalid scale in PFM header: {}", tokens[3])))?;
+    let n: usize = width as usize * height as usize * channels * 4;
+    // NOTE: A single whitespace separates the header and the binary data
+    let data: &[u8] = bytes.get(pos + 1..pos + 1 + n)
+        .ok_or_else(|| invalid_data("PFM data is too short".to_string()))?;
+    // Negative scale means little endian
+    let values: Vec<f32> = data.chunks(4)
+        .map(|b| {
+            let b: [u8; 4] = [b[0], b[1], b[2], b[3]];
+            if scale < 0.0 { f32::from_le_bytes(b) } else { f32::from_be_bytes(b) }
+        })
+        .collect();
+    let rows: Vec<Vec<Color3>> = values.chunks(width.max(1) as usize * channels)
+        .map(|row| row.chunks(channels)
+            .map(|p| if channels == 3 { Color3 {r: p[0], g: p[1], b: p[2]} } else { Color3 {r: p[0], g: p[0], b: p[0]} })
+            .collect())
+        .collect();
+    // NOTE: Rows are stored from bottom to top
+    Ok((width, height, rows.into_iter().rev().flatten().collect()))
+}
+
+// Read an image as linear colors (top row first)
+// NOTE: 8-bit images are converted from gamma 2, the inverse of the output conversion
+pub fn read_image(file_path: &path::Path) -> io::Result<(u32, u32, Vec<Color3>)> {
+    let mut bytes: Vec<u8> = Vec::new();
+    fs::File::open(file_path)?.read_to_end(&mut bytes)?;
+    let (width, height, rgb) = match ImageFormat::from_path(file_path) {
+        Some(ImageFormat::Ppm) => read_ppm(&bytes)?,
+        Some(ImageFormat::Png) => png::read_png(&mut bytes.as_slice())?,
+        Some(ImageFormat::Pfm) => return read_pfm(&bytes),
+        Some(ImageFormat::Hdr) => return radiance_hdr::read_hdr(&bytes),
+        _ => return Err(invalid_data(format!("unsupported image file: {}", file_path.display())))
+    };
+    let linear = |v: u8| {
+        let f: f32 = v as f32 / 255.0;
+        f * f
+    };
+    let colors: Vec<Color3> = rgb.chunks(3)
+        .map(|p| Color3 {r: linear(p[0]), g: linear(p[1]), b: linear(p[2])})
+        .collect();
+    Ok((width, height, colors))
+}
@@ -1,13 +1,16 @@
+use rand::prelude::*;
+use crate::vec3::Vec3;
 use crate::ray::Ray;
 use crate::hitable::Hitable;
 use crate::hitable::HitRecord;
+use crate::aabb::Aabb;
 
 pub struct ListHitable {
     pub hitables: std::vec::Vec<Box<dyn Hitable + Sync>>
 }
 
 impl Hitable for ListHitable {
-    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
         let mut closest_so_far: f32 = t_max;
         let mut hit_record_opt: Option<HitRecord> = None;
         for hitable in &self.hitables {
@@ -18,4 +21,27 @@ impl Hitable for ListHitable {
         }
         hit_record_opt
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let mut bbox_opt: Option<Aabb> = None;
+        for hitable in &self.hitables {
+            let bbox: Aabb = hitable.bounding_box()?;
+            bbox_opt = Some(match bbox_opt {
+                Some(b) => b.surrounding_box(&bbox),
+                None => bbox
+            });
+        }
+        bbox_opt
+    }
+
+    // Uniform mixture of the hitables
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        let sum: f32 = self.hitables.iter().map(|h| h.pdf_value(origin, direction)).sum();
+        sum / self.hitables.len() as f32
+    }
+
+    fn random(&self, rng: &mut rand::rngs::StdRng, origin: &Vec3) -> Vec3 {
+        let index: usize = rng.gen_range(0, self.hitables.len());
+        self.hitables[index].random(rng, origin)
+    }
 }
@@ -1,9 +1,12 @@
+// NOTE: Lints which the original code does not follow are allowed rather than rewriting it
+#![allow(clippy::redundant_field_names, clippy::single_component_path_imports, clippy::needless_borrow)]
+
 use std::io;
 use std::fs;
-use std::io::Write;
-use rand;
+use std::time::{Duration, Instant};
 use std::path::PathBuf;
 use structopt::StructOpt;
+use image_format::ImageFormat;
 
 mod color3;
 mod vec3;
@@ -17,7 +20,86 @@ mod util;
 mod render;
 mod random_scenes;
 mod scene;
+mod aabb;
+mod bvh_hitable;
+mod scene_file;
+mod png;
+mod image_format;
+mod radiance_hdr;
+mod exr;
+mod texture;
+mod perlin;
+mod triangle_hitable;
+mod mesh_hitable;
+mod obj;
+mod moving_sphere_hitable;
+mod onb;
+mod checkpoint;
+mod distributed;
+mod spectrum;
+mod constant_medium_hitable;
+mod rect_hitable;
+mod box_hitable;
+mod flip_normals_hitable;
+mod mat4;
+mod transform_hitable;
+mod csg_hitable;
+mod plane_hitable;
+mod disk_hitable;
+mod cylinder_hitable;
+mod cone_hitable;
+mod torus_hitable;
+mod sdf;
+mod sdf_hitable;
+mod background;
+
+#[derive(Debug)]
+enum BuiltinScene {
+    IowBookCover,
+    IowBookCoverNight,
+    CornellBox
+}
+
+impl std::str::FromStr for BuiltinScene {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "iow-book-cover" => Ok(BuiltinScene::IowBookCover),
+            "iow-book-cover-night" => Ok(BuiltinScene::IowBookCoverNight),
+            "cornell-box" => Ok(BuiltinScene::CornellBox),
+            _ => Err(format!("unknown scene: {} (available: iow-book-cover, iow-book-cover-night, cornell-box)", s))
+        }
+    }
+}
 
+impl BuiltinScene {
+    fn name(&self) -> &'static str {
+        match self {
+            BuiltinScene::IowBookCover => "iow-book-cover",
+            BuiltinScene::IowBookCoverNight => "iow-book-cover-night",
+            BuiltinScene::CornellBox => "cornell-box"
+        }
+    }
+
+    fn scene(&self, rng: &mut rand::rngs::StdRng, width: u32, height: u32) -> scene::Scene {
+        match self {
+            BuiltinScene::IowBookCover => random_scenes::iow_book_cover(rng, width, height),
+            BuiltinScene::IowBookCoverNight => random_scenes::iow_book_cover_night(rng, width, height),
+            BuiltinScene::CornellBox => random_scenes::cornell_box(width, height)
+        }
+    }
+}
+
+// Positive duration in seconds
+fn parse_timeout(s: &str) -> Result<Duration, String> {
+    let seconds: f32 = s.parse().map_err(|_| format!("invalid number: {}", s))?;
+    // NOTE: Sockets reject a zero timeout, which tiny values round to
+    match Duration::try_from_secs_f32(seconds) {
+        Ok(timeout) if seconds.is_finite() && !timeout.is_zero() => Ok(timeout),
+        _ => Err(format!("timeout must be a positive number of seconds: {}", s))
+    }
+}
 
 /// Ray Tracing in One Weekend in Rust
 #[derive(StructOpt, Debug)]
@@ -42,7 +124,67 @@ struct Opt {
 
     /// Random seed
     #[structopt(long, default_value = "101")]
-    random_seed: u8,
+    random_seed: u64,
+
+    /// Adaptive sampling: keep sampling a pixel after --n-samples until the standard error of its mean is below this ratio of the mean
+    #[structopt(long)]
+    adaptive_threshold: Option<f32>,
+
+    /// Maximum number of samples per pixel in adaptive sampling [default: 10 times --n-samples]
+    #[structopt(long)]
+    max_samples: Option<u32>,
+
+    /// Output file of a heat-map of the number of samples per pixel
+    #[structopt(long, parse(from_os_str))]
+    sample_heatmap: Option<PathBuf>,
+
+    /// Spectral rendering, tracing a wavelength per path for dispersion
+    #[structopt(long)]
+    spectral: bool,
+
+    /// Size of square tiles rendered in parallel
+    #[structopt(long, default_value = "32")]
+    tile_size: u32,
+
+    /// Number of samples per pixel in each pass over the image
+    #[structopt(long, default_value = "1")]
+    samples_per_pass: u32,
+
+    /// Interval in seconds to write the image in progress into the output file
+    #[structopt(long)]
+    preview_interval: Option<f32>,
+
+    /// Checkpoint file of the render in progress to resume it by --resume [default: the file of --resume]
+    #[structopt(long, parse(from_os_str))]
+    checkpoint: Option<PathBuf>,
+
+    /// Interval in seconds to write the checkpoint file
+    #[structopt(long, default_value = "60")]
+    checkpoint_interval: f32,
+
+    /// Resume the render of the checkpoint file with its settings, scene and output file
+    #[structopt(long, parse(from_os_str))]
+    resume: Option<PathBuf>,
+
+    /// Render by workers connecting to this address (e.g. 0.0.0.0:7878) instead of locally
+    #[structopt(long)]
+    coordinator: Option<String>,
+
+    /// Seconds the coordinator waits for a worker to render a tile before giving the tile to another worker
+    #[structopt(long, default_value = "600", parse(try_from_str = parse_timeout))]
+    worker_timeout: Duration,
+
+    /// Work for the coordinator at this address (e.g. 192.168.0.2:7878), which gives the settings and the scene
+    #[structopt(long)]
+    worker: Option<String>,
+
+    /// Built-in scene (iow-book-cover, iow-book-cover-night, cornell-box)
+    #[structopt(long, default_value = "iow-book-cover")]
+    builtin_scene: BuiltinScene,
+
+    /// Scene description file (JSON)
+    #[structopt(long, parse(from_os_str))]
+    scene: Option<PathBuf>,
 
     /// Animation output directory
     #[structopt(long)]
@@ -64,6 +206,22 @@ struct Opt {
     #[structopt(long, default_value = "6")]
     anime_skip_step: usize,
 
+    /// First frame number of the animation to render
+    #[structopt(long, default_value = "1")]
+    anime_first_frame: usize,
+
+    /// Last frame number of the animation to render [default: the last frame]
+    #[structopt(long)]
+    anime_last_frame: Option<usize>,
+
+    /// Animation shutter open time as a fraction of a frame, which blurs moving objects (0 for no motion blur)
+    #[structopt(long, default_value = "0.5")]
+    anime_shutter: f32,
+
+    /// Output image format (ppm, png, pfm, hdr, exr) [default: guessed from the file extension, or ppm]
+    #[structopt(long)]
+    format: Option<ImageFormat>,
+
     /// Output file path
     #[structopt(name = "FILE", parse(from_os_str))]
     file: Option<PathBuf>,
@@ -71,44 +229,191 @@ struct Opt {
 
 fn main() {
     // Parse options
-    let opt = Opt::from_args();
+    let mut opt = Opt::from_args();
+
+    // Work for the coordinator
+    if let Some(coordinator_addr) = &opt.worker {
+        if let Err(err) = distributed::work(coordinator_addr.as_str()) {
+            eprintln!("{}: {}", coordinator_addr, err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Restore the settings and the accumulation from the checkpoint
+    let resumed_accumulation: Option<render::Accumulation> = if let Some(resume_path) = &opt.resume {
+        let exit_with_error = |err: &dyn std::fmt::Display| -> ! {
+            eprintln!("{}: {}", resume_path.display(), err);
+            std::process::exit(1);
+        };
+        let (header, accumulation) = match checkpoint::read_checkpoint(resume_path) {
+            Ok(checkpoint) => checkpoint,
+            Err(err) => exit_with_error(&err)
+        };
+        opt.width = header.width;
+        opt.height = header.height;
+        opt.n_samples = header.n_samples;
+        opt.min_float = header.min_float;
+        opt.random_seed = header.random_seed;
+        opt.adaptive_threshold = header.adaptive_threshold;
+        opt.max_samples = header.max_samples;
+        opt.spectral = header.spectral;
+        // NOTE: A corrupt or edited header may have unknown names
+        opt.builtin_scene = header.builtin_scene.parse().unwrap_or_else(|err: String| exit_with_error(&err));
+        opt.scene = header.scene;
+        opt.format = Some(header.format.parse().unwrap_or_else(|err: String| exit_with_error(&err)));
+        opt.file = Some(header.file);
+        Some(accumulation)
+    } else {
+        None
+    };
+    let checkpoint_path: Option<PathBuf> = opt.checkpoint.clone().or_else(|| opt.resume.clone());
+
+    let settings = render::RenderSettings {
+        width: opt.width,
+        height: opt.height,
+        n_samples: opt.n_samples,
+        min_float: opt.min_float,
+        random_seed: opt.random_seed,
+        adaptive: opt.adaptive_threshold.map(|threshold| render::AdaptiveSampling {
+            threshold,
+            max_samples: opt.max_samples.unwrap_or(opt.n_samples * 10)
+        }),
+        tile_size: opt.tile_size.max(1),
+        samples_per_pass: opt.samples_per_pass.max(1),
+        preview_interval: opt.preview_interval.map(Duration::from_secs_f32),
+        spectral: opt.spectral
+    };
 
     // If render animation
     if let Some(anime_out_dir_path_str) = opt.anime_out_dir_path {
         // Get animation output directory path
         let anime_out_dir_path = std::path::Path::new(&anime_out_dir_path_str);
+        // NOTE: A frame is written every skip step + 1 steps of the simulation
+        let shutter: f32 = opt.anime_shutter * opt.anime_dt * (opt.anime_skip_step + 1) as f32;
         // Get scene iterator
-        let scene_iter: random_scenes::FreeFallAnimation = random_scenes::FreeFallAnimation::new(opt.width, opt.height, opt.anime_dt, opt.anime_min_t, opt.anime_max_t, opt.random_seed);
+        let scene_iter: random_scenes::FreeFallAnimation = random_scenes::FreeFallAnimation::new(opt.width, opt.height, opt.anime_dt, opt.anime_min_t, opt.anime_max_t, shutter, opt.random_seed);
         // Skip by step
         let scene_iter= util::skip_by_step(scene_iter, opt.anime_skip_step);
-        // Render animation frame by frame
-        render::render_animation(anime_out_dir_path, opt.random_seed, scene_iter, opt.width, opt.height, opt.n_samples, opt.min_float);
+        let format: ImageFormat = opt.format.unwrap_or(ImageFormat::Ppm);
+        let frames = opt.anime_first_frame..=opt.anime_last_frame.unwrap_or(usize::MAX);
+        if let Some(coordinator_addr) = &opt.coordinator {
+            // Render animation by workers
+            std::fs::create_dir_all(anime_out_dir_path).unwrap();
+            let pending_frames: Vec<u64> = (0..scene_iter.count())
+                .filter(|&idx| render::is_frame_pending(anime_out_dir_path, format, &frames, idx))
+                .map(|idx| idx as u64)
+                .collect();
+            let job = distributed::Job {
+                settings,
+                scene: distributed::SceneSpec::FreeFallAnimation {
+                    dt: opt.anime_dt,
+                    min_t: opt.anime_min_t,
+                    max_t: opt.anime_max_t,
+                    shutter,
+                    skip_step: opt.anime_skip_step
+                }
+            };
+            distributed::coordinate(coordinator_addr.as_str(), &job, &pending_frames, opt.worker_timeout, |frame, accumulation| {
+                let file_path = render::frame_path(anime_out_dir_path, format, frame as usize);
+                render::write_image_file(&file_path, format, job.settings.width, job.settings.height, &accumulation.image()).unwrap();
+                println!("{:?} rendered", file_path);
+            }).unwrap();
+        } else {
+            // Render animation frame by frame
+            render::render_animation(anime_out_dir_path, format, scene_iter, frames, &settings);
+        }
     } else {
-        // Select output destination whether file or stdout
-        // (from: https://users.rust-lang.org/t/how-to-create-bufreader---from-option-file-with-std-io-stdout-as-fallback-in-a-rust-way/12980/2?u=nwtgck)
-        let write: Box<Write> =
-            if let Some(file_path) = opt.file {
-                Box::new(fs::File::create(file_path).unwrap())
+        // Select output format by the option or the file extension
+        let format: ImageFormat = opt.format
+            .or_else(|| opt.file.as_ref().and_then(|file_path| ImageFormat::from_path(file_path)))
+            .unwrap_or(ImageFormat::Ppm);
+
+        let sample_counts: Vec<u32> = if let Some(coordinator_addr) = &opt.coordinator {
+            if checkpoint_path.is_some() {
+                eprintln!("checkpoints are not supported with --coordinator");
+                std::process::exit(1);
+            }
+            // Render by workers
+            let job = distributed::Job {
+                settings: settings.clone(),
+                scene: match &opt.scene {
+                    Some(scene_file_path) => distributed::SceneSpec::File {path: scene_file_path.clone()},
+                    None => distributed::SceneSpec::Builtin {name: opt.builtin_scene.name().to_string()}
+                }
+            };
+            let mut rendered: Option<render::Accumulation> = None;
+            distributed::coordinate(coordinator_addr.as_str(), &job, &[0], opt.worker_timeout, |_, accumulation| rendered = Some(accumulation)).unwrap();
+            let accumulation: render::Accumulation = rendered.unwrap();
+            if let Some(file_path) = &opt.file {
+                render::write_image_file(file_path, format, opt.width, opt.height, &accumulation.image()).unwrap();
+            } else {
+                image_format::write_image(&mut io::BufWriter::new(io::stdout()), format, opt.width, opt.height, &accumulation.image()).unwrap();
+            }
+            accumulation.sample_counts()
+        } else {
+            // Get random generator
+            let mut rng: rand::rngs::StdRng = util::rng_by_seed(opt.random_seed);
+
+            // Generate scene
+            let scene = if let Some(scene_file_path) = &opt.scene {
+                match scene_file::load(scene_file_path, opt.width, opt.height) {
+                    Ok(scene) => scene,
+                    Err(err) => {
+                        eprintln!("{}: {}", scene_file_path.display(), err);
+                        std::process::exit(1);
+                    }
+                }
             } else {
-                Box::new(io::stdout())
+                opt.builtin_scene.scene(&mut rng, opt.width, opt.height)
             };
-        let writer = io::BufWriter::new(write);
-
-        // Get random generator
-        let mut rng: rand::rngs::StdRng = util::rng_by_seed(opt.random_seed);
-
-        // Generate scene
-        let scene = random_scenes::iow_book_cover(&mut rng, opt.width, opt.height);
-
-        // Render by ray tracing
-        render::render(
-            writer,
-            opt.random_seed,
-            &scene,
-            opt.width,
-            opt.height,
-            opt.n_samples,
-            opt.min_float
-        );
+
+            // Render by ray tracing into the file, or stdout
+            if let Some(file_path) = &opt.file {
+                let accumulation: render::Accumulation = resumed_accumulation.unwrap_or_else(|| render::Accumulation::new(&settings));
+                let header = checkpoint::CheckpointHeader {
+                    width: opt.width,
+                    height: opt.height,
+                    n_samples: opt.n_samples,
+                    min_float: opt.min_float,
+                    random_seed: opt.random_seed,
+                    adaptive_threshold: opt.adaptive_threshold,
+                    max_samples: opt.max_samples,
+                    spectral: opt.spectral,
+                    builtin_scene: opt.builtin_scene.name().to_string(),
+                    scene: opt.scene.clone(),
+                    file: file_path.clone(),
+                    format: format.extension().to_string()
+                };
+                let checkpoint_interval: Duration = Duration::from_secs_f32(opt.checkpoint_interval);
+                let mut last_checkpoint: Instant = Instant::now();
+                let accumulation: render::Accumulation = render::render_to_file(file_path, format, &scene, 0, &settings, accumulation, |accumulation| {
+                    if let Some(checkpoint_path) = &checkpoint_path {
+                        if last_checkpoint.elapsed() >= checkpoint_interval {
+                            checkpoint::write_checkpoint(checkpoint_path, &header, accumulation)?;
+                            last_checkpoint = Instant::now();
+                        }
+                    }
+                    Ok(())
+                }).unwrap();
+                // Write the final state so that resuming a finished render only rewrites the image
+                if let Some(checkpoint_path) = &checkpoint_path {
+                    checkpoint::write_checkpoint(checkpoint_path, &header, &accumulation).unwrap();
+                }
+                accumulation.sample_counts()
+            } else {
+                if checkpoint_path.is_some() {
+                    eprintln!("a checkpoint requires an output file");
+                    std::process::exit(1);
+                }
+                render::render(io::BufWriter::new(io::stdout()), format, &scene, 0, &settings)
+            }
+        };
+
+        if let Some(heatmap_path) = opt.sample_heatmap {
+            let heatmap_format: ImageFormat = ImageFormat::from_path(&heatmap_path).unwrap_or(ImageFormat::Ppm);
+            let writer = io::BufWriter::new(fs::File::create(&heatmap_path).unwrap());
+            render::write_sample_heatmap(writer, heatmap_format, &settings, &sample_counts).unwrap();
+        }
     }
 }
@@ -0,0 +1,128 @@
+use std::ops::Mul;
+use crate::vec3::Vec3;
+
+// 4x4 matrix of an affine transform acting on column vectors (the last row is 0, 0, 0, 1)
+#[derive(Debug, Copy, Clone)]
+pub struct Mat4 {
+    pub m: [[f32; 4]; 4]
+}
+
+impl Mat4 {
+    pub fn identity() -> Mat4 {
+        let mut m: [[f32; 4]; 4] = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            row[i] = 1.0;
+        }
+        Mat4 {m}
+    }
+
+    pub fn translate(offset: &Vec3) -> Mat4 {
+        let mut matrix: Mat4 = Mat4::identity();
+        for axis in 0..3 {
+            matrix.m[axis][3] = offset[axis];
+        }
+        matrix
+    }
+
+    pub fn scale(factors: &Vec3) -> Mat4 {
+        let mut matrix: Mat4 = Mat4::identity();
+        for axis in 0..3 {
+            matrix.m[axis][axis] = factors[axis];
+        }
+        matrix
+    }
+
+    // Counterclockwise rotation around the axis by the angle in degrees (Rodrigues' rotation formula)
+    pub fn rotate(axis: &Vec3, degrees: f32) -> Mat4 {
+        let a: Vec3 = axis.unit_vector();
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let t: f32 = 1.0 - cos;
+        Mat4 {m: [
+            [t * a.x * a.x + cos,       t * a.x * a.y - sin * a.z, t * a.x * a.z + sin * a.y, 0.0],
+            [t * a.x * a.y + sin * a.z, t * a.y * a.y + cos,       t * a.y * a.z - sin * a.x, 0.0],
+            [t * a.x * a.z - sin * a.y, t * a.y * a.z + sin * a.x, t * a.z * a.z + cos,       0.0],
+            [0.0,                       0.0,                       0.0,                       1.0]
+        ]}
+    }
+
+    pub fn transpose(&self) -> Mat4 {
+        let mut m: [[f32; 4]; 4] = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.m[j][i];
+            }
+        }
+        Mat4 {m}
+    }
+
+    // Inverse by Gauss-Jordan elimination with partial pivoting (None if singular)
+    pub fn inverse(&self) -> Option<Mat4> {
+        let mut a: [[f32; 4]; 4] = self.m;
+        let mut inv: [[f32; 4]; 4] = Mat4::identity().m;
+        for col in 0..4 {
+            let pivot: usize = (col..4).max_by(|&i, &j| a[i][col].abs().partial_cmp(&a[j][col].abs()).unwrap())?;
+            if a[pivot][col].abs() < 1e-12 {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+            let scale: f32 = 1.0 / a[col][col];
+            for j in 0..4 {
+                a[col][j] *= scale;
+                inv[col][j] *= scale;
+            }
+            for i in 0..4 {
+                if i != col {
+                    let factor: f32 = a[i][col];
+                    for j in 0..4 {
+                        a[i][j] -= factor * a[col][j];
+                        inv[i][j] -= factor * inv[col][j];
+                    }
+                }
+            }
+        }
+        Some(Mat4 {m: inv})
+    }
+
+    // Determinant of the linear part (the upper-left 3x3 matrix)
+    pub fn linear_determinant(&self) -> f32 {
+        let m = &self.m;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    pub fn transform_point(&self, p: &Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3 {
+            x: m[0][0] * p.x + m[0][1] * p.y + m[0][2] * p.z + m[0][3],
+            y: m[1][0] * p.x + m[1][1] * p.y + m[1][2] * p.z + m[1][3],
+            z: m[2][0] * p.x + m[2][1] * p.y + m[2][2] * p.z + m[2][3]
+        }
+    }
+
+    // Transform of a direction, ignoring the translation
+    pub fn transform_vector(&self, v: &Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3 {
+            x: m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            y: m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            z: m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z
+        }
+    }
+}
+
+// Composition applying the right-hand side first
+impl Mul for &Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut m: [[f32; 4]; 4] = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.m[i][k] * rhs.m[k][j]).sum();
+            }
+        }
+        Mat4 {m}
+    }
+}
@@ -4,32 +4,60 @@ use crate::ray::Ray;
 use crate::hitable::HitRecord;
 use crate::vec3::Vec3;
 use crate::util;
+use crate::texture::Texture;
+use crate::onb::Onb;
 
 pub struct ScatterRecord{
+    // BSDF times the cosine term divided by the density of the scattered direction
     pub attenuation: Color3,
-    pub scattered: Ray
+    pub scattered: Ray,
+    // Density of the scattered direction (None for specular scattering, which light sampling does not apply to)
+    pub pdf: Option<f32>
 }
 
-pub trait Material {
+pub trait Material: Send {
     fn scatter(&self, rng: &mut rand::rngs::StdRng, r_in: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord>;
+    // BSDF times the cosine term towards the direction and the density of `scatter` choosing it
+    // (used to weight directions sampled towards lights)
+    fn scattering(&self, _r_in: &Ray, _hit_record: &HitRecord, _direction: &Vec3) -> (Color3, f32) {
+        (Color3 {r: 0.0, g: 0.0, b: 0.0}, 0.0)
+    }
+    // Emitted radiance (black for non-emissive materials)
+    fn emitted(&self, _hit_record: &HitRecord) -> Color3 {
+        Color3 {r: 0.0, g: 0.0, b: 0.0}
+    }
 }
 
 pub struct LambertMaterial {
-    pub albedo: Color3
+    pub albedo: Box<dyn Texture + Sync>
 }
 
 impl Material for LambertMaterial {
-    fn scatter(&self, rng: &mut rand::rngs::StdRng, _r_in: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
-        let target: Vec3 = &(&hit_record.p + &hit_record.normal) + &util::random_in_unit_sphere(rng);
+    fn scatter(&self, rng: &mut rand::rngs::StdRng, r_in: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        // Cosine-weighted direction, whose density cancels out the BSDF and the cosine term
+        let normal: Vec3 = facing_normal(r_in, hit_record);
+        let direction: Vec3 = Onb::from_w(&normal).local(&util::random_cosine_direction(rng));
         Some(ScatterRecord {
-            attenuation: self.albedo,
-            scattered: Ray{origin: hit_record.p, direction: &target - &hit_record.p}
+            attenuation: self.albedo.value(hit_record.u, hit_record.v, &hit_record.p),
+            pdf: Some(normal.dot(&direction).max(0.0) / std::f32::consts::PI),
+            scattered: Ray{origin: hit_record.p, direction, time: r_in.time, wavelength: r_in.wavelength}
         })
     }
+
+    fn scattering(&self, r_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> (Color3, f32) {
+        let cosine: f32 = facing_normal(r_in, hit_record).dot(&direction.unit_vector()).max(0.0);
+        let pdf: f32 = cosine / std::f32::consts::PI;
+        (&self.albedo.value(hit_record.u, hit_record.v, &hit_record.p) * pdf, pdf)
+    }
+}
+
+// Normal on the side where the ray comes from
+fn facing_normal(r_in: &Ray, hit_record: &HitRecord) -> Vec3 {
+    if r_in.direction.dot(&hit_record.normal) > 0.0 { -&hit_record.normal } else { hit_record.normal }
 }
 
 pub struct MetalMaterial {
-    pub albedo: Color3,
+    pub albedo: Box<dyn Texture + Sync>,
     pub f: f32
 }
 
@@ -67,12 +95,15 @@ impl Material for MetalMaterial {
         let reflected: Vec3 = reflect(&r_in.direction.unit_vector(), &hit_record.normal);
         let scattered: Ray  = Ray {
             origin: hit_record.p,
-            direction: &reflected + &(&util::random_in_unit_sphere(rng) * self.fuzz())
+            direction: &reflected + &(&util::random_in_unit_sphere(rng) * self.fuzz()),
+            time: r_in.time,
+            wavelength: r_in.wavelength
         };
         if scattered.direction.dot(&hit_record.normal) > 0.0 {
             Some(ScatterRecord {
-                attenuation: self.albedo,
-                scattered: scattered
+                attenuation: self.albedo.value(hit_record.u, hit_record.v, &hit_record.p),
+                scattered: scattered,
+                pdf: None
             })
         } else {
             None
@@ -80,39 +111,444 @@ impl Material for MetalMaterial {
     }
 }
 
+// Conductor with a measured complex index of refraction
+#[derive(Clone, Copy)]
+pub enum Conductor {
+    Gold,
+    Copper,
+    Aluminium
+}
+
+impl Conductor {
+    // Complex index of refraction (eta + ik) at about 650, 550 and 450 nm for red, green and blue
+    pub fn ior(&self) -> (Color3, Color3) {
+        match self {
+            Conductor::Gold => (Color3 {r: 0.143, g: 0.374, b: 1.442}, Color3 {r: 3.983, g: 2.385, b: 1.603}),
+            Conductor::Copper => (Color3 {r: 0.200, g: 0.924, b: 1.102}, Color3 {r: 3.912, g: 2.452, b: 2.142}),
+            Conductor::Aluminium => (Color3 {r: 1.657, g: 0.880, b: 0.521}, Color3 {r: 9.224, g: 6.270, b: 4.837})
+        }
+    }
+}
+
+// Fresnel reflectance of a conductor for unpolarized light
+fn fresnel_conductor(cos_theta: f32, eta: f32, k: f32) -> f32 {
+    let cos2: f32 = cos_theta * cos_theta;
+    let sin2: f32 = 1.0 - cos2;
+    let t0: f32 = eta * eta - k * k - sin2;
+    let a2_plus_b2: f32 = (t0 * t0 + 4.0 * eta * eta * k * k).sqrt();
+    let a: f32 = (0.5 * (a2_plus_b2 + t0)).max(0.0).sqrt();
+    let t1: f32 = a2_plus_b2 + cos2;
+    let t2: f32 = 2.0 * cos_theta * a;
+    let rs: f32 = (t1 - t2) / (t1 + t2);
+    let t3: f32 = cos2 * a2_plus_b2 + sin2 * sin2;
+    let t4: f32 = t2 * sin2;
+    let rp: f32 = rs * (t3 - t4) / (t3 + t4);
+    0.5 * (rs + rp)
+}
+
+// GGX (Trowbridge-Reitz) distribution of microfacet normals
+// NOTE: Directions are in the ONB of the facing normal, whose u and v axes are the axes of anisotropy
+#[derive(Clone, Copy)]
+pub struct GgxDistribution {
+    // Width of the distribution along the u and v axes
+    pub alpha_u: f32,
+    pub alpha_v: f32
+}
+
+impl GgxDistribution {
+    // NOTE: A smaller width breaks the distribution in single precision
+    const MIN_ALPHA: f32 = 0.001;
+
+    // Distribution by the perceptual roughness in [0, 1] and the anisotropy in [0, 1), which stretches highlights along v
+    pub fn new(roughness: f32, anisotropy: f32) -> GgxDistribution {
+        let aspect: f32 = (1.0 - 0.9 * anisotropy.clamp(0.0, 1.0)).sqrt();
+        let alpha: f32 = roughness * roughness;
+        GgxDistribution {
+            alpha_u: (alpha / aspect).max(GgxDistribution::MIN_ALPHA),
+            alpha_v: (alpha * aspect).max(GgxDistribution::MIN_ALPHA)
+        }
+    }
+
+    // Density of normals
+    fn d(&self, m: &Vec3) -> f32 {
+        let e: f32 = (m.x / self.alpha_u).powi(2) + (m.y / self.alpha_v).powi(2) + m.z * m.z;
+        1.0 / (std::f32::consts::PI * self.alpha_u * self.alpha_v * e * e)
+    }
+
+    // Smith's auxiliary function for the masking of the direction
+    fn lambda(&self, w: &Vec3) -> f32 {
+        let tan2: f32 = ((self.alpha_u * w.x).powi(2) + (self.alpha_v * w.y).powi(2)) / (w.z * w.z);
+        0.5 * ((1.0 + tan2).sqrt() - 1.0)
+    }
+
+    // Masking of the direction
+    fn g1(&self, w: &Vec3) -> f32 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    // Masking and shadowing of the pair of directions on either side
+    fn g2(&self, wo: &Vec3, wi: &Vec3) -> f32 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    // Normal visible from the direction wo (Heitz, "Sampling the GGX Distribution of Visible Normals", 2018),
+    // whose density is g1(wo) * wo.dot(m) * d(m) / wo.z
+    fn sample_visible_normal(&self, rng: &mut rand::rngs::StdRng, wo: &Vec3) -> Vec3 {
+        // Direction in the hemisphere configuration
+        let vh: Vec3 = Vec3 {x: self.alpha_u * wo.x, y: self.alpha_v * wo.y, z: wo.z}.unit_vector();
+        let len2: f32 = vh.x * vh.x + vh.y * vh.y;
+        let t1: Vec3 = if len2 > 0.0 {
+            &Vec3 {x: -vh.y, y: vh.x, z: 0.0} / len2.sqrt()
+        } else {
+            Vec3 {x: 1.0, y: 0.0, z: 0.0}
+        };
+        let t2: Vec3 = vh.cross(&t1);
+        // Point on the projected disk
+        let r: f32 = rng.gen::<f32>().sqrt();
+        let phi: f32 = 2.0 * std::f32::consts::PI * rng.gen::<f32>();
+        let p1: f32 = r * phi.cos();
+        let s: f32 = 0.5 * (1.0 + vh.z);
+        let p2: f32 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * r * phi.sin();
+        let nh: Vec3 = &(&(&t1 * p1) + &(&t2 * p2)) + &(&vh * (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt());
+        Vec3 {x: self.alpha_u * nh.x, y: self.alpha_v * nh.y, z: nh.z.max(0.0)}.unit_vector()
+    }
+}
+
+// Rough metal by the GGX microfacet model, sampling visible normals
+pub struct MicrofacetMetalMaterial {
+    // Complex index of refraction (eta + ik)
+    pub eta: Color3,
+    pub k: Color3,
+    pub distribution: GgxDistribution,
+    // Direction in world coordinates projected onto the surface as the v axis of anisotropy
+    pub anisotropy_axis: Vec3
+}
+
+impl MicrofacetMetalMaterial {
+    fn onb(&self, r_in: &Ray, hit_record: &HitRecord) -> Onb {
+        Onb::from_w_and_v(&facing_normal(r_in, hit_record), &self.anisotropy_axis)
+    }
+
+    fn fresnel(&self, cos_theta: f32) -> Color3 {
+        Color3 {
+            r: fresnel_conductor(cos_theta, self.eta.r, self.k.r),
+            g: fresnel_conductor(cos_theta, self.eta.g, self.k.g),
+            b: fresnel_conductor(cos_theta, self.eta.b, self.k.b)
+        }
+    }
+}
+
+impl Material for MicrofacetMetalMaterial {
+    fn scatter(&self, rng: &mut rand::rngs::StdRng, r_in: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let onb: Onb = self.onb(r_in, hit_record);
+        let wo: Vec3 = onb.to_local(&-&r_in.direction.unit_vector());
+        if wo.z <= 0.0 {
+            return None;
+        }
+        let distribution: &GgxDistribution = &self.distribution;
+        let m: Vec3 = distribution.sample_visible_normal(rng, &wo);
+        let wi: Vec3 = reflect(&-&wo, &m);
+        // NOTE: Light reflected below the surface would be reflected again by other microfacets, which is ignored
+        if wi.z <= 0.0 {
+            return None;
+        }
+        Some(ScatterRecord {
+            attenuation: &self.fresnel(wo.dot(&m)) * (distribution.g2(&wo, &wi) / distribution.g1(&wo)),
+            scattered: Ray {origin: hit_record.p, direction: onb.local(&wi), time: r_in.time, wavelength: r_in.wavelength},
+            pdf: Some(distribution.g1(&wo) * distribution.d(&m) / (4.0 * wo.z))
+        })
+    }
+
+    fn scattering(&self, r_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> (Color3, f32) {
+        let onb: Onb = self.onb(r_in, hit_record);
+        let wo: Vec3 = onb.to_local(&-&r_in.direction.unit_vector());
+        let wi: Vec3 = onb.to_local(&direction.unit_vector());
+        if wo.z <= 0.0 || wi.z <= 0.0 {
+            return (Color3 {r: 0.0, g: 0.0, b: 0.0}, 0.0);
+        }
+        let distribution: &GgxDistribution = &self.distribution;
+        let m: Vec3 = (&wo + &wi).unit_vector();
+        let d: f32 = distribution.d(&m);
+        // BSDF F D G2 / (4 cos_o cos_i) times cos_i
+        (
+            &self.fresnel(wo.dot(&m)) * (d * distribution.g2(&wo, &wi) / (4.0 * wo.z)),
+            distribution.g1(&wo) * d / (4.0 * wo.z)
+        )
+    }
+}
+
+// Fresnel reflectance of a dielectric for unpolarized light (eta is the ratio of the index of refraction beyond the surface)
+fn fresnel_dielectric(cos_theta: f32, eta: f32) -> f32 {
+    let sin2_t: f32 = (1.0 - cos_theta * cos_theta) / (eta * eta);
+    // Total internal reflection
+    if sin2_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_t: f32 = (1.0 - sin2_t).sqrt();
+    let rs: f32 = (cos_theta - eta * cos_t) / (cos_theta + eta * cos_t);
+    let rp: f32 = (eta * cos_theta - cos_t) / (eta * cos_theta + cos_t);
+    0.5 * (rs * rs + rp * rp)
+}
+
+// Transmittance through the medium of the absorption coefficient from the previous point of the ray if it hits the surface from inside
+// (Beer-Lambert law)
+// NOTE: The previous point of the ray is on the surface of the medium unless another object is inside it
+fn transmittance(absorption: &Option<Color3>, r_in: &Ray, hit_record: &HitRecord) -> Color3 {
+    match absorption {
+        Some(absorption) if r_in.direction.dot(&hit_record.normal) > 0.0 => {
+            let distance: f32 = hit_record.t * r_in.direction.length();
+            Color3 {
+                r: (-absorption.r * distance).exp(),
+                g: (-absorption.g * distance).exp(),
+                b: (-absorption.b * distance).exp()
+            }
+        },
+        _ => Color3 {r: 1.0, g: 1.0, b: 1.0}
+    }
+}
+
+// Index of refraction depending on the wavelength (in micrometres in the formulas)
+#[derive(Clone, Copy)]
+pub enum Dispersion {
+    // n = a + b / λ^2
+    Cauchy {a: f32, b: f32},
+    // n^2 = 1 + Σ b_i λ^2 / (λ^2 - c_i)
+    Sellmeier {b: [f32; 3], c: [f32; 3]}
+}
+
+impl Dispersion {
+    // Index of refraction at the wavelength in nanometres
+    pub fn ior(&self, wavelength: f32) -> f32 {
+        let l2: f32 = (wavelength / 1000.0).powi(2);
+        match self {
+            Dispersion::Cauchy {a, b} => a + b / l2,
+            Dispersion::Sellmeier {b, c} => {
+                (1.0 + (0..3).map(|i| b[i] * l2 / (l2 - c[i])).sum::<f32>()).sqrt()
+            }
+        }
+    }
+}
+
+// Index of refraction for the wavelength of the ray, which is `ref_idx` without dispersion or a wavelength
+fn ref_idx_of_ray(ref_idx: f32, dispersion: &Option<Dispersion>, r_in: &Ray) -> f32 {
+    match (dispersion, r_in.wavelength) {
+        (Some(dispersion), Some(wavelength)) => dispersion.ior(wavelength),
+        _ => ref_idx
+    }
+}
+
 pub struct DielectricMaterial {
-    pub ref_idx: f32
+    pub ref_idx: f32,
+    // Absorption coefficient per unit distance inside (None for clear glass)
+    pub absorption: Option<Color3>,
+    // Dispersion in spectral rendering
+    pub dispersion: Option<Dispersion>
 }
 
 impl Material for DielectricMaterial {
     fn scatter(&self, rng: &mut rand::rngs::StdRng, r_in: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
         let reflected: Vec3 = reflect(&r_in.direction, &hit_record.normal);
-        let attenuation: Color3 = Color3 {r: 1.0, g: 1.0, b: 1.0};
+        let attenuation: Color3 = transmittance(&self.absorption, r_in, hit_record);
+        let ref_idx: f32 = ref_idx_of_ray(self.ref_idx, &self.dispersion, r_in);
         let (outward_normal, ni_over_nt, cosine) =
             if r_in.direction.dot(&hit_record.normal) > 0.0 {
-                let cosine: f32 = self.ref_idx * r_in.direction.dot(&hit_record.normal) / r_in.direction.length();
-                (-&hit_record.normal, self.ref_idx, cosine)
+                let cosine: f32 = ref_idx * r_in.direction.dot(&hit_record.normal) / r_in.direction.length();
+                (-&hit_record.normal, ref_idx, cosine)
             } else {
                 let cosine: f32 = -r_in.direction.dot(&hit_record.normal) / r_in.direction.length();
-                (hit_record.normal, 1.0 / self.ref_idx, cosine)
+                (hit_record.normal, 1.0 / ref_idx, cosine)
             };
 
-        let reflect_prob: f32 = schlick(cosine, self.ref_idx);
+        let reflect_prob: f32 = schlick(cosine, ref_idx);
 
         let r: f32 = rng.gen();
         match refract(&r_in.direction, &outward_normal, ni_over_nt) {
             Some(refracted) if reflect_prob <= r => {
                 Some(ScatterRecord {
                     attenuation,
-                    scattered: Ray{origin: hit_record.p, direction: refracted}
+                    scattered: Ray{origin: hit_record.p, direction: refracted, time: r_in.time, wavelength: r_in.wavelength},
+                    pdf: None
                 })
             },
             _ => {
                Some(ScatterRecord {
                     attenuation,
-                    scattered: Ray{origin: hit_record.p, direction: reflected}
+                    scattered: Ray{origin: hit_record.p, direction: reflected, time: r_in.time, wavelength: r_in.wavelength},
+                    pdf: None
                 })
             }
         }
     }
 }
+
+// Rough dielectric such as frosted glass by the GGX microfacet model, sampling visible normals (Walter et al., 2007)
+// NOTE: Transmission is not scaled by the ratio of the indices of refraction as DielectricMaterial
+pub struct RoughDielectricMaterial {
+    pub ref_idx: f32,
+    // Absorption coefficient per unit distance inside (None for clear glass)
+    pub absorption: Option<Color3>,
+    // Dispersion in spectral rendering
+    pub dispersion: Option<Dispersion>,
+    pub distribution: GgxDistribution
+}
+
+impl RoughDielectricMaterial {
+    // Ratio of the index of refraction beyond the surface to the index on the side of the ray
+    fn eta(&self, r_in: &Ray, hit_record: &HitRecord) -> f32 {
+        let ref_idx: f32 = ref_idx_of_ray(self.ref_idx, &self.dispersion, r_in);
+        if r_in.direction.dot(&hit_record.normal) > 0.0 { 1.0 / ref_idx } else { ref_idx }
+    }
+
+    // BSDF times the cosine term and the density of the direction wi in the ONB of the facing normal
+    fn scattering_local(&self, wo: &Vec3, wi: &Vec3, eta: f32) -> (f32, f32) {
+        let distribution: &GgxDistribution = &self.distribution;
+        if wi.z > 0.0 {
+            let m: Vec3 = (wo + wi).unit_vector();
+            let d: f32 = distribution.d(&m);
+            let f: f32 = fresnel_dielectric(wo.dot(&m), eta);
+            (
+                f * d * distribution.g2(wo, wi) / (4.0 * wo.z),
+                f * distribution.g1(wo) * d / (4.0 * wo.z)
+            )
+        } else if wi.z < 0.0 {
+            // Generalized half vector on the side of wo
+            let m: Vec3 = (wo + &(wi * eta)).unit_vector();
+            let m: Vec3 = if m.z < 0.0 { -&m } else { m };
+            let cos_o: f32 = wo.dot(&m);
+            let cos_i: f32 = wi.dot(&m);
+            if cos_o <= 0.0 || cos_i >= 0.0 {
+                return (0.0, 0.0);
+            }
+            let d: f32 = distribution.d(&m);
+            let f: f32 = fresnel_dielectric(cos_o, eta);
+            // Jacobian of the half vector by the refracted direction
+            let jacobian: f32 = eta * eta * -cos_i / (cos_o + eta * cos_i).powi(2);
+            (
+                (1.0 - f) * d * distribution.g2(wo, wi) * cos_o * jacobian / wo.z,
+                (1.0 - f) * distribution.g1(wo) * d * cos_o * jacobian / wo.z
+            )
+        } else {
+            (0.0, 0.0)
+        }
+    }
+}
+
+impl Material for RoughDielectricMaterial {
+    fn scatter(&self, rng: &mut rand::rngs::StdRng, r_in: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let onb: Onb = Onb::from_w(&facing_normal(r_in, hit_record));
+        let wo: Vec3 = onb.to_local(&-&r_in.direction.unit_vector());
+        if wo.z <= 0.0 {
+            return None;
+        }
+        let eta: f32 = self.eta(r_in, hit_record);
+        let distribution: &GgxDistribution = &self.distribution;
+        let m: Vec3 = distribution.sample_visible_normal(rng, &wo);
+        let cos_o: f32 = wo.dot(&m);
+        let f: f32 = fresnel_dielectric(cos_o, eta);
+        // Reflect or refract by the Fresnel reflectance, which cancels out in the weight
+        let wi: Vec3 = if rng.gen::<f32>() < f {
+            let wi: Vec3 = reflect(&-&wo, &m);
+            if wi.z <= 0.0 {
+                return None;
+            }
+            wi
+        } else {
+            let cos_t: f32 = (1.0 - (1.0 - cos_o * cos_o) / (eta * eta)).max(0.0).sqrt();
+            let wi: Vec3 = &(&-&wo / eta) + &(&m * (cos_o / eta - cos_t));
+            if wi.z >= 0.0 {
+                return None;
+            }
+            wi
+        };
+        let (_, pdf) = self.scattering_local(&wo, &wi, eta);
+        Some(ScatterRecord {
+            attenuation: &transmittance(&self.absorption, r_in, hit_record) * (distribution.g2(&wo, &wi) / distribution.g1(&wo)),
+            scattered: Ray {origin: hit_record.p, direction: onb.local(&wi), time: r_in.time, wavelength: r_in.wavelength},
+            pdf: Some(pdf)
+        })
+    }
+
+    fn scattering(&self, r_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> (Color3, f32) {
+        let onb: Onb = Onb::from_w(&facing_normal(r_in, hit_record));
+        let wo: Vec3 = onb.to_local(&-&r_in.direction.unit_vector());
+        let wi: Vec3 = onb.to_local(&direction.unit_vector());
+        if wo.z <= 0.0 {
+            return (Color3 {r: 0.0, g: 0.0, b: 0.0}, 0.0);
+        }
+        let (value, pdf) = self.scattering_local(&wo, &wi, self.eta(r_in, hit_record));
+        (&transmittance(&self.absorption, r_in, hit_record) * value, pdf)
+    }
+}
+
+// Phase function scattering uniformly in all directions inside participating media
+pub struct IsotropicMaterial {
+    pub albedo: Box<dyn Texture + Sync>
+}
+
+impl Material for IsotropicMaterial {
+    fn scatter(&self, rng: &mut rand::rngs::StdRng, r_in: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        Some(ScatterRecord {
+            attenuation: self.albedo.value(hit_record.u, hit_record.v, &hit_record.p),
+            scattered: Ray {origin: hit_record.p, direction: util::random_unit_vector(rng), time: r_in.time, wavelength: r_in.wavelength},
+            pdf: Some(1.0 / (4.0 * std::f32::consts::PI))
+        })
+    }
+
+    fn scattering(&self, _r_in: &Ray, hit_record: &HitRecord, _direction: &Vec3) -> (Color3, f32) {
+        let pdf: f32 = 1.0 / (4.0 * std::f32::consts::PI);
+        (&self.albedo.value(hit_record.u, hit_record.v, &hit_record.p) * pdf, pdf)
+    }
+}
+
+// Henyey-Greenstein phase function, scattering forward for positive `g` and backward for negative `g` in (-1, 1)
+pub struct HenyeyGreensteinMaterial {
+    pub albedo: Box<dyn Texture + Sync>,
+    pub g: f32
+}
+
+impl HenyeyGreensteinMaterial {
+    // Density of the angle whose cosine is between the directions of the incoming and the scattered rays
+    fn phase(&self, cos_theta: f32) -> f32 {
+        let denominator: f32 = 1.0 + self.g * self.g - 2.0 * self.g * cos_theta;
+        (1.0 - self.g * self.g) / (4.0 * std::f32::consts::PI * denominator * denominator.sqrt())
+    }
+}
+
+impl Material for HenyeyGreensteinMaterial {
+    fn scatter(&self, rng: &mut rand::rngs::StdRng, r_in: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let g: f32 = self.g;
+        let cos_theta: f32 = if g.abs() < 1e-3 {
+            1.0 - 2.0 * rng.gen::<f32>()
+        } else {
+            let s: f32 = (1.0 - g * g) / (1.0 - g + 2.0 * g * rng.gen::<f32>());
+            ((1.0 + g * g - s * s) / (2.0 * g)).clamp(-1.0, 1.0)
+        };
+        let sin_theta: f32 = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi: f32 = 2.0 * std::f32::consts::PI * rng.gen::<f32>();
+        let direction: Vec3 = Onb::from_w(&r_in.direction).local(&Vec3 {x: sin_theta * phi.cos(), y: sin_theta * phi.sin(), z: cos_theta});
+        Some(ScatterRecord {
+            attenuation: self.albedo.value(hit_record.u, hit_record.v, &hit_record.p),
+            scattered: Ray {origin: hit_record.p, direction, time: r_in.time, wavelength: r_in.wavelength},
+            pdf: Some(self.phase(cos_theta))
+        })
+    }
+
+    fn scattering(&self, r_in: &Ray, hit_record: &HitRecord, direction: &Vec3) -> (Color3, f32) {
+        let pdf: f32 = self.phase(r_in.direction.unit_vector().dot(&direction.unit_vector()));
+        (&self.albedo.value(hit_record.u, hit_record.v, &hit_record.p) * pdf, pdf)
+    }
+}
+
+pub struct DiffuseLightMaterial {
+    pub emit: Color3
+}
+
+impl Material for DiffuseLightMaterial {
+    fn scatter(&self, _rng: &mut rand::rngs::StdRng, _r_in: &Ray, _hit_record: &HitRecord) -> Option<ScatterRecord> {
+        None
+    }
+
+    fn emitted(&self, _hit_record: &HitRecord) -> Color3 {
+        self.emit
+    }
+}
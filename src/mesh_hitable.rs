@@ -0,0 +1,83 @@
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::hitable::Hitable;
+use crate::hitable::HitRecord;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::bvh_hitable::Bvh;
+use crate::triangle_hitable;
+use core::borrow::Borrow;
+
+// Triangle referring to vertex buffers of a mesh by indices
+pub struct MeshFace {
+    pub positions: [usize; 3],
+    pub normals: Option<[usize; 3]>,
+    pub uvs: Option<[usize; 3]>,
+    pub material: usize
+}
+
+// Triangle mesh sharing vertex buffers between faces, accelerated by its own BVH
+pub struct MeshHitable {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(f32, f32)>,
+    faces: Vec<MeshFace>,
+    materials: Vec<Box<dyn Material + Sync>>,
+    bvh: Bvh
+}
+
+impl MeshHitable {
+    // NOTE: Indices in faces should be valid in the buffers
+    pub fn new(positions: Vec<Vec3>, normals: Vec<Vec3>, uvs: Vec<(f32, f32)>, faces: Vec<MeshFace>, materials: Vec<Box<dyn Material + Sync>>) -> MeshHitable {
+        let bboxes: Vec<Aabb> = faces.iter().map(|face| {
+            let [i0, i1, i2] = face.positions;
+            triangle_hitable::triangle_bounding_box(&positions[i0], &positions[i1], &positions[i2])
+        }).collect();
+        MeshHitable {
+            bvh: Bvh::new(&bboxes),
+            positions,
+            normals,
+            uvs,
+            faces,
+            materials
+        }
+    }
+
+    fn hit_face(&self, face: &MeshFace, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let [i0, i1, i2] = face.positions;
+        let (p0, p1, p2) = (&self.positions[i0], &self.positions[i1], &self.positions[i2]);
+        let (t, b1, b2) = triangle_hitable::hit_triangle(r, p0, p1, p2, t_min, t_max)?;
+        let geometric_normal: Vec3 = (p1 - p0).cross(&(p2 - p0)).unit_vector();
+        let normal: Vec3 = match face.normals {
+            Some([n0, n1, n2]) => triangle_hitable::shading_normal(
+                &geometric_normal,
+                [&self.normals[n0], &self.normals[n1], &self.normals[n2]],
+                b1,
+                b2
+            ),
+            None => geometric_normal
+        };
+        let (u, v) = match face.uvs {
+            Some([t0, t1, t2]) => triangle_hitable::interpolate_uv([self.uvs[t0], self.uvs[t1], self.uvs[t2]], b1, b2),
+            None => (b1, b2)
+        };
+        Some(HitRecord {
+            t,
+            p: r.point_at_parameter(t),
+            normal,
+            u,
+            v,
+            material: self.materials[face.material].borrow()
+        })
+    }
+}
+
+impl Hitable for MeshHitable {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        self.bvh.hit(r, t_min, t_max, |index, t_max| self.hit_face(&self.faces[index], r, t_min, t_max))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bvh.bounding_box()
+    }
+}
@@ -0,0 +1,65 @@
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::hitable::Hitable;
+use crate::hitable::HitRecord;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::sphere_hitable::sphere_uv;
+use core::borrow::Borrow;
+
+// Sphere moving linearly from center0 at time0 to center1 at time1
+pub struct MovingSphereHitable {
+    pub center0: Vec3,
+    pub center1: Vec3,
+    pub time0: f32,
+    pub time1: f32,
+    pub radius: f32,
+    pub material: Box<dyn Material + std::marker::Sync>
+}
+
+impl MovingSphereHitable {
+    // NOTE: The sphere stays at center0 before time0 and at center1 after time1, within the bounding box
+    pub fn center(&self, time: f32) -> Vec3 {
+        if self.time1 <= self.time0 {
+            return self.center0;
+        }
+        let s: f32 = ((time - self.time0) / (self.time1 - self.time0)).clamp(0.0, 1.0);
+        &self.center0 + &(&(&self.center1 - &self.center0) * s)
+    }
+}
+
+impl Hitable for MovingSphereHitable {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let center: Vec3 = self.center(r.time);
+        let oc: Vec3 = &r.origin - &center;
+        let a : f32  = r.direction.dot(&r.direction);
+        let b : f32  = oc.dot(&r.direction);
+        let c : f32  = oc.dot(&oc) - self.radius*self.radius;
+        let discriminant: f32 = b * b - a * c;
+        if discriminant <= 0.0 {
+            return None;
+        }
+
+        let temp1: f32 = (-b - discriminant.sqrt()) / a;
+        let temp2: f32 = (-b + discriminant.sqrt()) / a;
+        let t: f32 = if t_min < temp1 && temp1 < t_max {
+            temp1
+        } else if t_min < temp2 && temp2 < t_max {
+            temp2
+        } else {
+            return None;
+        };
+        let p: Vec3 = r.point_at_parameter(t);
+        let normal: Vec3 = &(&p - &center) / self.radius;
+        let (u, v) = sphere_uv(&normal);
+        Some(HitRecord{ t, p, normal, u, v, material: self.material.borrow()})
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // Box containing the sphere over the whole motion
+        let r: Vec3 = Vec3 {x: self.radius, y: self.radius, z: self.radius};
+        let box0: Aabb = Aabb {min: &self.center0 - &r, max: &self.center0 + &r};
+        let box1: Aabb = Aabb {min: &self.center1 - &r, max: &self.center1 + &r};
+        Some(box0.surrounding_box(&box1))
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path;
+
+use crate::color3::Color3;
+use crate::vec3::Vec3;
+use crate::material::{Material, LambertMaterial, MetalMaterial, DielectricMaterial, DiffuseLightMaterial};
+use crate::texture::{Texture, ConstantTexture, ImageTexture};
+use crate::mesh_hitable::{MeshHitable, MeshFace};
+use crate::image_format;
+
+// Wavefront OBJ loader with MTL materials
+
+fn invalid_data(line_no: usize, message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line_no, message))
+}
+
+fn parse_floats<const N: usize>(line_no: usize, args: &[&str]) -> io::Result<[f32; N]> {
+    let mut values: [f32; N] = [0.0; N];
+    for (i, value) in values.iter_mut().enumerate() {
+        let arg: &str = args.get(i).ok_or_else(|| invalid_data(line_no, "too few values"))?;
+        *value = arg.parse().map_err(|_| invalid_data(line_no, &format!("invalid number: {}", arg)))?;
+    }
+    Ok(values)
+}
+
+// Material parameters in a MTL file
+struct MtlMaterial {
+    kd: Color3,
+    ks: Color3,
+    ke: Color3,
+    ns: f32,
+    ni: f32,
+    dissolve: f32,
+    illum: u32,
+    map_kd: Option<path::PathBuf>
+}
+
+impl Default for MtlMaterial {
+    fn default() -> Self {
+        MtlMaterial {
+            kd: Color3 {r: 0.8, g: 0.8, b: 0.8},
+            ks: Color3 {r: 0.0, g: 0.0, b: 0.0},
+            ke: Color3 {r: 0.0, g: 0.0, b: 0.0},
+            ns: 0.0,
+            ni: 1.0,
+            dissolve: 1.0,
+            illum: 2,
+            map_kd: None
+        }
+    }
+}
+
+fn is_black(c: &Color3) -> bool {
+    c.r <= 0.0 && c.g <= 0.0 && c.b <= 0.0
+}
+
+impl MtlMaterial {
+    // Map onto the materials of this ray tracer
+    fn to_material(&self) -> io::Result<Box<dyn Material + Sync>> {
+        Ok(if !is_black(&self.ke) {
+            Box::new(DiffuseLightMaterial {emit: self.ke})
+        } else if [4, 6, 7, 9].contains(&self.illum) || self.dissolve < 1.0 {
+            // NOTE: Ni is often left 1.0 for glass
+            Box::new(DielectricMaterial {ref_idx: if self.ni > 1.0 { self.ni } else { 1.5 }, absorption: None, dispersion: None})
+        } else if self.illum == 3 || (is_black(&self.kd) && !is_black(&self.ks)) {
+            // Roughness from the Phong exponent
+            Box::new(MetalMaterial {
+                albedo: Box::new(ConstantTexture {color: self.ks}),
+                f: (2.0 / (self.ns + 2.0)).sqrt()
+            })
+        } else {
+            let albedo: Box<dyn Texture + Sync> = match &self.map_kd {
+                Some(file_path) => {
+                    let (width, height, pixels) = image_format::read_image(file_path)?;
+                    Box::new(ImageTexture {width, height, pixels})
+                },
+                None => Box::new(ConstantTexture {color: self.kd})
+            };
+            Box::new(LambertMaterial {albedo})
+        })
+    }
+}
+
+fn load_mtl(file_path: &path::Path) -> io::Result<HashMap<String, MtlMaterial>> {
+    let text: String = fs::read_to_string(file_path)?;
+    let base_dir: &path::Path = file_path.parent().unwrap_or_else(|| path::Path::new("."));
+    let mut materials: HashMap<String, MtlMaterial> = HashMap::new();
+    let mut current: Option<String> = None;
+    for (idx, line) in text.lines().enumerate() {
+        let line_no: usize = idx + 1;
+        let mut tokens = line.split_whitespace();
+        let keyword: &str = match tokens.next() {
+            Some(k) if !k.starts_with('#') => k,
+            _ => continue
+        };
+        let args: Vec<&str> = tokens.collect();
+        if keyword == "newmtl" {
+            let name: String = args.join(" ");
+            materials.insert(name.clone(), MtlMaterial::default());
+            current = Some(name);
+            continue;
+        }
+        let material: &mut MtlMaterial = match &current {
+            Some(name) => materials.get_mut(name).unwrap(),
+            None => return Err(invalid_data(line_no, "material parameter before newmtl"))
+        };
+        let color = |args: &[&str]| parse_floats::<3>(line_no, args).map(|[r, g, b]| Color3 {r, g, b});
+        match keyword {
+            "Kd" => material.kd = color(&args)?,
+            "Ks" => material.ks = color(&args)?,
+            "Ke" => material.ke = color(&args)?,
+            "Ns" => material.ns = parse_floats::<1>(line_no, &args)?[0],
+            "Ni" => material.ni = parse_floats::<1>(line_no, &args)?[0],
+            "d" => material.dissolve = parse_floats::<1>(line_no, &args)?[0],
+            "Tr" => material.dissolve = 1.0 - parse_floats::<1>(line_no, &args)?[0],
+            "illum" => {
+                material.illum = args.first()
+                    .and_then(|a| a.parse().ok())
+                    .ok_or_else(|| invalid_data(line_no, "invalid illum"))?;
+            },
+            // NOTE: Options of the texture map are not supported, so the last argument is the file name
+            "map_Kd" => material.map_kd = args.last().map(|name| base_dir.join(name)),
+            _ => {}
+        }
+    }
+    Ok(materials)
+}
+
+// Resolve a 1-based (or negative, relative to the end) OBJ index
+fn resolve_index(line_no: usize, index: &str, len: usize) -> io::Result<usize> {
+    let i: i64 = index.parse().map_err(|_| invalid_data(line_no, &format!("invalid index: {}", index)))?;
+    let resolved: i64 = if i < 0 { len as i64 + i } else { i - 1 };
+    if 0 <= resolved && resolved < len as i64 {
+        Ok(resolved as usize)
+    } else {
+        Err(invalid_data(line_no, &format!("index out of range: {}", index)))
+    }
+}
+
+// Load an OBJ file as a mesh, where faces without materials use the default material
+pub fn load_obj(file_path: &path::Path, default_material: Box<dyn Material + Sync>) -> io::Result<MeshHitable> {
+    let text: String = fs::read_to_string(file_path)?;
+    let base_dir: &path::Path = file_path.parent().unwrap_or_else(|| path::Path::new("."));
+
+    let mut positions: Vec<Vec3> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<(f32, f32)> = Vec::new();
+    let mut faces: Vec<MeshFace> = Vec::new();
+    let mut mtl_materials: HashMap<String, MtlMaterial> = HashMap::new();
+    // Materials used by faces and their indices
+    let mut materials: Vec<Box<dyn Material + Sync>> = vec![default_material];
+    let mut material_indices: HashMap<String, usize> = HashMap::new();
+    let mut current_material: usize = 0;
+
+    for (idx, line) in text.lines().enumerate() {
+        let line_no: usize = idx + 1;
+        let mut tokens = line.split_whitespace();
+        let keyword: &str = match tokens.next() {
+            Some(k) if !k.starts_with('#') => k,
+            _ => continue
+        };
+        let args: Vec<&str> = tokens.collect();
+        match keyword {
+            "v" => {
+                let [x, y, z] = parse_floats::<3>(line_no, &args)?;
+                positions.push(Vec3 {x, y, z});
+            },
+            "vn" => {
+                let [x, y, z] = parse_floats::<3>(line_no, &args)?;
+                normals.push(Vec3 {x, y, z});
+            },
+            "vt" => {
+                let [u, v] = parse_floats::<2>(line_no, &args)?;
+                uvs.push((u, v));
+            },
+            "f" => {
+                if args.len() < 3 {
+                    return Err(invalid_data(line_no, "face with less than 3 vertices"));
+                }
+                // Vertex as position/uv/normal indices
+                let mut vertices: Vec<(usize, Option<usize>, Option<usize>)> = Vec::new();
+                for arg in &args {
+                    let mut parts = arg.split('/');
+                    let position: usize = resolve_index(line_no, parts.next().unwrap_or(""), positions.len())?;
+                    let uv: Option<usize> = match parts.next() {
+                        Some(s) if !s.is_empty() => Some(resolve_index(line_no, s, uvs.len())?),
+                        _ => None
+                    };
+                    let normal: Option<usize> = match parts.next() {
+                        Some(s) if !s.is_empty() => Some(resolve_index(line_no, s, normals.len())?),
+                        _ => None
+                    };
+                    vertices.push((position, uv, normal));
+                }
+                // Triangulate a polygon as a fan
+                for i in 1..vertices.len() - 1 {
+                    let tri = [vertices[0], vertices[i], vertices[i + 1]];
+                    let all_uvs: Option<Vec<usize>> = tri.iter().map(|v| v.1).collect();
+                    let all_normals: Option<Vec<usize>> = tri.iter().map(|v| v.2).collect();
+                    faces.push(MeshFace {
+                        positions: [tri[0].0, tri[1].0, tri[2].0],
+                        normals: all_normals.map(|n| [n[0], n[1], n[2]]),
+                        uvs: all_uvs.map(|t| [t[0], t[1], t[2]]),
+                        material: current_material
+                    });
+                }
+            },
+            "mtllib" => {
+                for name in &args {
+                    let mtl_path: path::PathBuf = base_dir.join(name);
+                    let loaded = load_mtl(&mtl_path)
+                        .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", mtl_path.display(), err)))?;
+                    mtl_materials.extend(loaded);
+                }
+            },
+            "usemtl" => {
+                let name: String = args.join(" ");
+                current_material = match material_indices.get(&name) {
+                    Some(&index) => index,
+                    None => {
+                        let mtl_material: &MtlMaterial = mtl_materials.get(&name)
+                            .ok_or_else(|| invalid_data(line_no, &format!("unknown material: {}", name)))?;
+                        materials.push(mtl_material.to_material()?);
+                        material_indices.insert(name, materials.len() - 1);
+                        materials.len() - 1
+                    }
+                };
+            },
+            // Groups, objects, smoothing groups and others are ignored
+            _ => {}
+        }
+    }
+    Ok(MeshHitable::new(positions, normals, uvs, faces, materials))
+}
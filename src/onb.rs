@@ -0,0 +1,42 @@
+use crate::vec3::Vec3;
+
+// Orthonormal basis whose w axis is the given direction
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3
+}
+
+impl Onb {
+    pub fn from_w(n: &Vec3) -> Onb {
+        let w: Vec3 = n.unit_vector();
+        let a: Vec3 = if w.x.abs() > 0.9 { Vec3 {x: 0.0, y: 1.0, z: 0.0} } else { Vec3 {x: 1.0, y: 0.0, z: 0.0} };
+        let v: Vec3 = w.cross(&a).unit_vector();
+        let u: Vec3 = w.cross(&v);
+        Onb {u, v, w}
+    }
+
+    // Basis whose v axis is the direction projected onto the plane perpendicular to w,
+    // which follows the surface instead of flipping at arbitrary boundaries
+    // NOTE: Falls back to from_w where the direction is (nearly) parallel to w
+    pub fn from_w_and_v(n: &Vec3, direction: &Vec3) -> Onb {
+        let w: Vec3 = n.unit_vector();
+        let projected: Vec3 = direction - &(&w * direction.dot(&w));
+        if projected.squared_length() < 1e-8 * direction.squared_length() {
+            return Onb::from_w(n);
+        }
+        let v: Vec3 = projected.unit_vector();
+        let u: Vec3 = v.cross(&w);
+        Onb {u, v, w}
+    }
+
+    // Coordinates in this basis of a vector in world coordinates
+    pub fn to_local(&self, a: &Vec3) -> Vec3 {
+        Vec3 {x: a.dot(&self.u), y: a.dot(&self.v), z: a.dot(&self.w)}
+    }
+
+    // Vector in world coordinates from coordinates in this basis
+    pub fn local(&self, a: &Vec3) -> Vec3 {
+        &(&(&self.u * a.x) + &(&self.v * a.y)) + &(&self.w * a.z)
+    }
+}
@@ -0,0 +1,85 @@
+use rand::prelude::*;
+use crate::vec3::Vec3;
+
+// Perlin noise with random unit gradient vectors
+pub struct Perlin {
+    ranvec: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>
+}
+
+impl Perlin {
+    const POINT_COUNT: usize = 256;
+
+    pub fn new(rng: &mut rand::rngs::StdRng) -> Perlin {
+        let ranvec: Vec<Vec3> = (0..Self::POINT_COUNT).map(|_| {
+            Vec3 {
+                x: 2.0 * rng.gen::<f32>() - 1.0,
+                y: 2.0 * rng.gen::<f32>() - 1.0,
+                z: 2.0 * rng.gen::<f32>() - 1.0
+            }.unit_vector()
+        }).collect();
+        Perlin {
+            ranvec,
+            perm_x: Self::generate_perm(rng),
+            perm_y: Self::generate_perm(rng),
+            perm_z: Self::generate_perm(rng)
+        }
+    }
+
+    fn generate_perm(rng: &mut rand::rngs::StdRng) -> Vec<usize> {
+        let mut p: Vec<usize> = (0..Self::POINT_COUNT).collect();
+        p.shuffle(rng);
+        p
+    }
+
+    // Noise in [-1, 1]
+    pub fn noise(&self, p: &Vec3) -> f32 {
+        let u: f32 = p.x - p.x.floor();
+        let v: f32 = p.y - p.y.floor();
+        let w: f32 = p.z - p.z.floor();
+        let i: i32 = p.x.floor() as i32;
+        let j: i32 = p.y.floor() as i32;
+        let k: i32 = p.z.floor() as i32;
+
+        // Hermite cubic smoothing
+        let uu: f32 = u * u * (3.0 - 2.0 * u);
+        let vv: f32 = v * v * (3.0 - 2.0 * v);
+        let ww: f32 = w * w * (3.0 - 2.0 * w);
+
+        let mask: i32 = Self::POINT_COUNT as i32 - 1;
+        let mut accum: f32 = 0.0;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let c: &Vec3 = &self.ranvec[
+                        self.perm_x[((i + di) & mask) as usize] ^
+                        self.perm_y[((j + dj) & mask) as usize] ^
+                        self.perm_z[((k + dk) & mask) as usize]
+                    ];
+                    let (fi, fj, fk) = (di as f32, dj as f32, dk as f32);
+                    let weight: Vec3 = Vec3 {x: u - fi, y: v - fj, z: w - fk};
+                    accum += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                        * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                        * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                        * c.dot(&weight);
+                }
+            }
+        }
+        accum
+    }
+
+    // Sum of noise over octaves
+    pub fn turbulence(&self, p: &Vec3, depth: u32) -> f32 {
+        let mut accum: f32 = 0.0;
+        let mut temp_p: Vec3 = *p;
+        let mut weight: f32 = 1.0;
+        for _ in 0..depth {
+            accum += weight * self.noise(&temp_p);
+            weight *= 0.5;
+            temp_p = &temp_p * 2.0;
+        }
+        accum.abs()
+    }
+}
@@ -0,0 +1,49 @@
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::hitable::Hitable;
+use crate::hitable::HitRecord;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::onb::Onb;
+use core::borrow::Borrow;
+
+// t where the ray crosses the plane through the point with the normal
+pub fn hit_plane(r: &Ray, point: &Vec3, normal: &Vec3, t_min: f32, t_max: f32) -> Option<f32> {
+    let t: f32 = (point - &r.origin).dot(normal) / r.direction.dot(normal);
+    // NOTE: Written in this way so that NaN (rays in the plane) is rejected
+    if t_min < t && t < t_max {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+// Infinite plane through the point
+pub struct PlaneHitable {
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub material: Box<dyn Material + std::marker::Sync>
+}
+
+impl Hitable for PlaneHitable {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let normal: Vec3 = self.normal.unit_vector();
+        let t: f32 = hit_plane(r, &self.point, &normal, t_min, t_max)?;
+        let p: Vec3 = r.point_at_parameter(t);
+        // Surface coordinates repeat every unit distance along axes in the plane
+        let onb: Onb = Onb::from_w(&normal);
+        let d: Vec3 = &p - &self.point;
+        Some(HitRecord {
+            t,
+            p,
+            normal,
+            u: d.dot(&onb.u).rem_euclid(1.0),
+            v: d.dot(&onb.v).rem_euclid(1.0),
+            material: self.material.borrow()
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+}
@@ -0,0 +1,415 @@
+use std::io;
+use std::io::Write;
+
+// Minimal dependency-free PNG encoder for 8-bit RGB images and decoder for non-interlaced images
+// NOTE: Image data is stored in uncompressed deflate blocks when encoding
+
+fn crc32_table() -> [u32; 256] {
+    let mut table: [u32; 256] = [0; 256];
+    for (n, entry) in table.iter_mut().enumerate() {
+        let mut c: u32 = n as u32;
+        for _ in 0..8 {
+            c = if c & 1 != 0 { 0xedb8_8320 ^ (c >> 1) } else { c >> 1 };
+        }
+        *entry = c;
+    }
+    table
+}
+
+fn crc32(table: &[u32; 256], bytes: &[u8]) -> u32 {
+    let mut c: u32 = 0xffff_ffff;
+    for &b in bytes {
+        c = table[((c ^ b as u32) & 0xff) as usize] ^ (c >> 8);
+    }
+    c ^ 0xffff_ffff
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let mut a: u32 = 1;
+    let mut b: u32 = 0;
+    // NOTE: 5552 is the largest n such that sums do not overflow before taking the modulo
+    for chunk in bytes.chunks(5552) {
+        for &x in chunk {
+            a += x as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+// zlib stream with stored (uncompressed) deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK_SIZE: usize = 65535;
+    let mut out: Vec<u8> = Vec::with_capacity(data.len() + data.len() / MAX_BLOCK_SIZE * 5 + 16);
+    // CMF (deflate, 32K window) and FLG (no dictionary, check bits)
+    out.extend_from_slice(&[0x78, 0x01]);
+    let mut blocks = data.chunks(MAX_BLOCK_SIZE).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[0x01, 0x00, 0x00, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final: u8 = if blocks.peek().is_none() { 1 } else { 0 };
+        let len: u16 = block.len() as u16;
+        out.push(is_final);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn write_chunk<W: Write>(writer: &mut W, table: &[u32; 256], chunk_type: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    let mut type_and_data: Vec<u8> = Vec::with_capacity(4 + data.len());
+    type_and_data.extend_from_slice(chunk_type);
+    type_and_data.extend_from_slice(data);
+    writer.write_all(&type_and_data)?;
+    writer.write_all(&crc32(table, &type_and_data).to_be_bytes())
+}
+
+// Write RGB pixels (top row first, 3 bytes per pixel) as PNG
+pub fn write_png<W: Write>(writer: &mut W, width: u32, height: u32, rgb: &[u8]) -> io::Result<()> {
+    let table: [u32; 256] = crc32_table();
+    writer.write_all(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'])?;
+
+    let mut ihdr: Vec<u8> = Vec::new();
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    // bit depth 8, color type 2 (RGB), compression 0, filter 0, no interlace
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+    write_chunk(writer, &table, b"IHDR", &ihdr)?;
+
+    // Each scanline starts with filter type 0 (None)
+    let row_len: usize = width as usize * 3;
+    let mut raw: Vec<u8> = Vec::with_capacity((row_len + 1) * height as usize);
+    for row in rgb.chunks(row_len) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    write_chunk(writer, &table, b"IDAT", &zlib_stored(&raw))?;
+    write_chunk(writer, &table, b"IEND", &[])
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+// Reader of deflate bits (least significant bit first)
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit: u32
+}
+
+impl<'a> BitReader<'a> {
+    fn bits(&mut self, n: u32) -> io::Result<u32> {
+        let mut v: u32 = 0;
+        for i in 0..n {
+            let byte: u8 = *self.data.get(self.pos).ok_or_else(|| invalid_data("unexpected end of deflate stream"))?;
+            v |= (((byte >> self.bit) & 1) as u32) << i;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.pos += 1;
+            }
+        }
+        Ok(v)
+    }
+
+    fn align_to_byte(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+}
+
+// Canonical Huffman code
+// (based on puff.c by Mark Adler)
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts: [u16; 16] = [0; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets: [u16; 16] = [0; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols: Vec<u16> = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Huffman {counts, symbols}
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> io::Result<u16> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for len in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count: i32 = self.counts[len] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + (code - first)) as usize]);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err(invalid_data("invalid Huffman code"))
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DIST_BASE: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DIST_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+fn inflate_codes(reader: &mut BitReader, out: &mut Vec<u8>, lit_len: &Huffman, dist: &Huffman) -> io::Result<()> {
+    loop {
+        let symbol: usize = lit_len.decode(reader)? as usize;
+        if symbol < 256 {
+            out.push(symbol as u8);
+        } else if symbol == 256 {
+            return Ok(());
+        } else {
+            let idx: usize = symbol - 257;
+            if idx >= LENGTH_BASE.len() {
+                return Err(invalid_data("invalid length symbol"));
+            }
+            let len: usize = LENGTH_BASE[idx] as usize + reader.bits(LENGTH_EXTRA[idx] as u32)? as usize;
+            let dist_symbol: usize = dist.decode(reader)? as usize;
+            if dist_symbol >= DIST_BASE.len() {
+                return Err(invalid_data("invalid distance symbol"));
+            }
+            let d: usize = DIST_BASE[dist_symbol] as usize + reader.bits(DIST_EXTRA[dist_symbol] as u32)? as usize;
+            if d > out.len() {
+                return Err(invalid_data("distance too far back"));
+            }
+            let start: usize = out.len() - d;
+            for i in 0..len {
+                let b: u8 = out[start + i];
+                out.push(b);
+            }
+        }
+    }
+}
+
+fn inflate(data: &[u8]) -> io::Result<Vec<u8>> {
+    const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+    let mut reader = BitReader {data, pos: 0, bit: 0};
+    let mut out: Vec<u8> = Vec::new();
+    loop {
+        let is_final: u32 = reader.bits(1)?;
+        match reader.bits(2)? {
+            // Stored
+            0 => {
+                reader.align_to_byte();
+                let header: &[u8] = data.get(reader.pos..reader.pos + 4).ok_or_else(|| invalid_data("unexpected end of stored block"))?;
+                let len: usize = u16::from_le_bytes([header[0], header[1]]) as usize;
+                reader.pos += 4;
+                let block: &[u8] = data.get(reader.pos..reader.pos + len).ok_or_else(|| invalid_data("unexpected end of stored block"))?;
+                out.extend_from_slice(block);
+                reader.pos += len;
+            },
+            // Fixed Huffman codes
+            1 => {
+                let mut lengths: [u8; 288] = [0; 288];
+                for (symbol, len) in lengths.iter_mut().enumerate() {
+                    *len = match symbol {
+                        0..=143 => 8,
+                        144..=255 => 9,
+                        256..=279 => 7,
+                        _ => 8
+                    };
+                }
+                inflate_codes(&mut reader, &mut out, &Huffman::new(&lengths), &Huffman::new(&[5; 30]))?;
+            },
+            // Dynamic Huffman codes
+            2 => {
+                let n_lit_len: usize = reader.bits(5)? as usize + 257;
+                let n_dist: usize = reader.bits(5)? as usize + 1;
+                let n_code_len: usize = reader.bits(4)? as usize + 4;
+                let mut code_lengths: [u8; 19] = [0; 19];
+                for &idx in CODE_LENGTH_ORDER.iter().take(n_code_len) {
+                    code_lengths[idx] = reader.bits(3)? as u8;
+                }
+                let code_len_huffman: Huffman = Huffman::new(&code_lengths);
+                let mut lengths: Vec<u8> = Vec::with_capacity(n_lit_len + n_dist);
+                while lengths.len() < n_lit_len + n_dist {
+                    let symbol: u16 = code_len_huffman.decode(&mut reader)?;
+                    let (value, repeat): (u8, u32) = match symbol {
+                        0..=15 => (symbol as u8, 1),
+                        16 => (*lengths.last().ok_or_else(|| invalid_data("no previous code length"))?, 3 + reader.bits(2)?),
+                        17 => (0, 3 + reader.bits(3)?),
+                        _ => (0, 11 + reader.bits(7)?)
+                    };
+                    for _ in 0..repeat {
+                        lengths.push(value);
+                    }
+                }
+                if lengths.len() > n_lit_len + n_dist {
+                    return Err(invalid_data("too many code lengths"));
+                }
+                let lit_len: Huffman = Huffman::new(&lengths[..n_lit_len]);
+                let dist: Huffman = Huffman::new(&lengths[n_lit_len..]);
+                inflate_codes(&mut reader, &mut out, &lit_len, &dist)?;
+            },
+            _ => return Err(invalid_data("invalid deflate block type"))
+        }
+        if is_final == 1 {
+            return Ok(out);
+        }
+    }
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p: i16 = a as i16 + b as i16 - c as i16;
+    let pa: i16 = (p - a as i16).abs();
+    let pb: i16 = (p - b as i16).abs();
+    let pc: i16 = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+// Read a PNG image as RGB pixels (top row first, 3 bytes per pixel)
+// NOTE: 16-bit samples are truncated to 8 bits and alpha is ignored
+pub fn read_png<R: io::Read>(reader: &mut R) -> io::Result<(u32, u32, Vec<u8>)> {
+    let mut bytes: Vec<u8> = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    if bytes.len() < 8 || bytes[..8] != [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'] {
+        return Err(invalid_data("not a PNG file"));
+    }
+
+    let mut pos: usize = 8;
+    let mut ihdr: Option<(u32, u32, u8, u8, u8)> = None;
+    let mut palette: Vec<u8> = Vec::new();
+    let mut idat: Vec<u8> = Vec::new();
+    while pos + 8 <= bytes.len() {
+        let len: usize = u32::from_be_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]]) as usize;
+        let chunk_type: &[u8] = &bytes[pos + 4..pos + 8];
+        let data: &[u8] = bytes.get(pos + 8..pos + 8 + len).ok_or_else(|| invalid_data("unexpected end of chunk"))?;
+        match chunk_type {
+            b"IHDR" if data.len() >= 13 => {
+                let width: u32 = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+                let height: u32 = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+                ihdr = Some((width, height, data[8], data[9], data[12]));
+            },
+            b"PLTE" => palette = data.to_vec(),
+            b"IDAT" => idat.extend_from_slice(data),
+            b"IEND" => break,
+            _ => {}
+        }
+        // length, type, data and CRC
+        pos += 12 + len;
+    }
+
+    let (width, height, bit_depth, color_type, interlace) = ihdr.ok_or_else(|| invalid_data("IHDR chunk not found"))?;
+    if interlace != 0 {
+        return Err(invalid_data("interlaced PNG is not supported"));
+    }
+    let channels: usize = match color_type {
+        0 => 1,
+        2 => 3,
+        3 => 1,
+        4 => 2,
+        6 => 4,
+        _ => return Err(invalid_data("invalid color type"))
+    };
+    if !(bit_depth == 8 || (bit_depth == 16 && color_type != 3)) {
+        return Err(invalid_data("only 8-bit and 16-bit PNG are supported"));
+    }
+    let bytes_per_sample: usize = bit_depth as usize / 8;
+    let bpp: usize = channels * bytes_per_sample;
+    let stride: usize = width as usize * bpp;
+
+    // Skip the zlib header
+    let raw: Vec<u8> = inflate(idat.get(2..).ok_or_else(|| invalid_data("no image data"))?)?;
+    if raw.len() < (stride + 1) * height as usize {
+        return Err(invalid_data("image data is too short"));
+    }
+
+    // Reverse scanline filters
+    let mut unfiltered: Vec<u8> = vec![0; stride * height as usize];
+    for y in 0..height as usize {
+        let filter_type: u8 = raw[y * (stride + 1)];
+        let line: &[u8] = &raw[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        for x in 0..stride {
+            let a: u8 = if x >= bpp { unfiltered[y * stride + x - bpp] } else { 0 };
+            let b: u8 = if y > 0 { unfiltered[(y - 1) * stride + x] } else { 0 };
+            let c: u8 = if x >= bpp && y > 0 { unfiltered[(y - 1) * stride + x - bpp] } else { 0 };
+            unfiltered[y * stride + x] = line[x].wrapping_add(match filter_type {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err(invalid_data("invalid filter type"))
+            });
+        }
+    }
+
+    // Convert to RGB taking the most significant byte of each sample
+    let mut rgb: Vec<u8> = Vec::with_capacity(width as usize * height as usize * 3);
+    for pixel in unfiltered.chunks(bpp) {
+        let sample = |i: usize| pixel[i * bytes_per_sample];
+        match color_type {
+            0 | 4 => rgb.extend_from_slice(&[sample(0), sample(0), sample(0)]),
+            3 => {
+                let idx: usize = sample(0) as usize * 3;
+                rgb.extend_from_slice(palette.get(idx..idx + 3).ok_or_else(|| invalid_data("palette index out of range"))?);
+            },
+            _ => rgb.extend_from_slice(&[sample(0), sample(1), sample(2)])
+        }
+    }
+    Ok((width, height, rgb))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(width: u32, height: u32) {
+        let rgb: Vec<u8> = (0..width * height * 3).map(|i| (i * 7 % 251) as u8).collect();
+        let mut bytes: Vec<u8> = Vec::new();
+        write_png(&mut bytes, width, height, &rgb).unwrap();
+        let (w, h, decoded) = read_png(&mut bytes.as_slice()).unwrap();
+        assert_eq!((w, h), (width, height));
+        assert_eq!(decoded, rgb);
+    }
+
+    #[test]
+    fn round_trip_small_image() {
+        round_trip(5, 3);
+    }
+
+    #[test]
+    fn round_trip_image_over_several_stored_blocks() {
+        round_trip(160, 150);
+    }
+
+    #[test]
+    fn reject_non_png() {
+        assert!(read_png(&mut &b"P6\n1 1\n255\n\0\0\0"[..]).is_err());
+    }
+}
@@ -0,0 +1,252 @@
+use std::io;
+use std::io::Write;
+
+use crate::color3::Color3;
+
+// Radiance RGBE (.hdr) encoder and decoder with run-length encoded scanlines
+
+fn rgbe(col: &Color3) -> [u8; 4] {
+    let v: f32 = col.r.max(col.g).max(col.b);
+    if v.is_nan() || v < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+    // v = m * 2^e (0.5 <= m < 1)
+    let mut e: i32 = v.log2().floor() as i32 + 1;
+    if v / 2f32.powi(e) >= 1.0 {
+        e += 1;
+    }
+    let scale: f32 = 256.0 / 2f32.powi(e);
+    let to_byte = |c: f32| (c.max(0.0) * scale).min(255.0) as u8;
+    [to_byte(col.r), to_byte(col.g), to_byte(col.b), (e + 128).clamp(0, 255) as u8]
+}
+
+fn from_rgbe(p: &[u8]) -> Color3 {
+    if p[3] == 0 {
+        return Color3 {r: 0.0, g: 0.0, b: 0.0};
+    }
+    // Bytes are mantissas by 256 with the exponent biased by 128 (rounded to the middle of the step)
+    let scale: f32 = 2f32.powi(p[3] as i32 - 136);
+    Color3 {r: (p[0] as f32 + 0.5) * scale, g: (p[1] as f32 + 0.5) * scale, b: (p[2] as f32 + 0.5) * scale}
+}
+
+// Run-length encode one component of a scanline
+// (based on rgbe.c by Bruce Walter)
+fn write_rle_component<W: Write>(writer: &mut W, data: &[u8]) -> io::Result<()> {
+    const MIN_RUN_LENGTH: usize = 4;
+    let mut cur: usize = 0;
+    while cur < data.len() {
+        // Find the next run of length at least MIN_RUN_LENGTH if one exists
+        let mut beg_run: usize = cur;
+        let mut run_count: usize = 0;
+        let mut old_run_count: usize = 0;
+        while run_count < MIN_RUN_LENGTH && beg_run < data.len() {
+            beg_run += run_count;
+            old_run_count = run_count;
+            run_count = 1;
+            while beg_run + run_count < data.len() && run_count < 127 && data[beg_run] == data[beg_run + run_count] {
+                run_count += 1;
+            }
+        }
+        // Write a short run before the next run as a run
+        if old_run_count > 1 && old_run_count == beg_run - cur {
+            writer.write_all(&[128 + old_run_count as u8, data[cur]])?;
+            cur = beg_run;
+        }
+        // Write bytes until the start of the next run
+        while cur < beg_run {
+            let n: usize = (beg_run - cur).min(128);
+            writer.write_all(&[n as u8])?;
+            writer.write_all(&data[cur..cur + n])?;
+            cur += n;
+        }
+        // Write the next run if one was found
+        if run_count >= MIN_RUN_LENGTH {
+            writer.write_all(&[128 + run_count as u8, data[beg_run]])?;
+            cur += run_count;
+        }
+    }
+    Ok(())
+}
+
+// Write linear colors (top row first)
+pub fn write_hdr<W: Write>(writer: &mut W, width: u32, height: u32, colors: &[Color3]) -> io::Result<()> {
+    writer.write_all(format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n", height, width).as_bytes())?;
+    let pixels: Vec<[u8; 4]> = colors.iter().map(rgbe).collect();
+    for row in pixels.chunks(width.max(1) as usize) {
+        // NOTE: Run-length encoding is only defined for these widths
+        if !(8..=0x7fff).contains(&width) {
+            for p in row {
+                writer.write_all(p)?;
+            }
+            continue;
+        }
+        writer.write_all(&[2, 2, (width >> 8) as u8, (width & 0xff) as u8])?;
+        for component in 0..4 {
+            let data: Vec<u8> = row.iter().map(|p| p[component]).collect();
+            write_rle_component(writer, &data)?;
+        }
+    }
+    Ok(())
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// Read a line of the header without the newline
+fn read_line<'a>(bytes: &'a [u8], pos: &mut usize) -> io::Result<&'a [u8]> {
+    let start: usize = *pos;
+    let length: usize = bytes[start..].iter().position(|&b| b == b'\n')
+        .ok_or_else(|| invalid_data("unexpected end of HDR header".to_string()))?;
+    *pos = start + length + 1;
+    Ok(&bytes[start..start + length])
+}
+
+// Decode one run-length encoded component of a scanline
+fn read_rle_component(bytes: &[u8], pos: &mut usize, width: usize) -> io::Result<Vec<u8>> {
+    let too_short = || invalid_data("HDR data is too short".to_string());
+    let mut data: Vec<u8> = Vec::with_capacity(width);
+    while data.len() < width {
+        let count: usize = *bytes.get(*pos).ok_or_else(too_short)? as usize;
+        // A count above 128 is a run of one byte, otherwise it is followed by the bytes
+        let n: usize = if count > 128 { count - 128 } else { count };
+        if n == 0 || data.len() + n > width {
+            return Err(invalid_data("invalid run length in HDR scanline".to_string()));
+        }
+        if count > 128 {
+            let value: u8 = *bytes.get(*pos + 1).ok_or_else(too_short)?;
+            data.extend(std::iter::repeat(value).take(n));
+            *pos += 2;
+        } else {
+            data.extend_from_slice(bytes.get(*pos + 1..*pos + 1 + n).ok_or_else(too_short)?);
+            *pos += 1 + n;
+        }
+    }
+    Ok(data)
+}
+
+// Read linear colors (top row first)
+// NOTE: Only the standard orientation (-Y height +X width) and scanlines either flat or run-length encoded
+// by components are supported, not the old run-length encoding
+pub fn read_hdr(bytes: &[u8]) -> io::Result<(u32, u32, Vec<Color3>)> {
+    let mut pos: usize = 0;
+    let magic: &[u8] = read_line(bytes, &mut pos)?;
+    if !magic.starts_with(b"#?") {
+        return Err(invalid_data("not a Radiance HDR file".to_string()));
+    }
+    // Variables until an empty line
+    loop {
+        let line: String = String::from_utf8_lossy(read_line(bytes, &mut pos)?).to_string();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(format) = line.strip_prefix("FORMAT=") {
+            if format != "32-bit_rle_rgbe" {
+                return Err(invalid_data(format!("unsupported HDR format: {}", format)));
+            }
+        }
+    }
+    let resolution: String = String::from_utf8_lossy(read_line(bytes, &mut pos)?).to_string();
+    let (height, width) = match resolution.split_ascii_whitespace().collect::<Vec<&str>>().as_slice() {
+        ["-Y", height, "+X", width] => (
+            height.parse::<u32>().map_err(|_| invalid_data(format!("invalid HDR resolution: {}", resolution)))?,
+            width.parse::<u32>().map_err(|_| invalid_data(format!("invalid HDR resolution: {}", resolution)))?
+        ),
+        _ => return Err(invalid_data(format!("unsupported HDR resolution: {}", resolution)))
+    };
+    let w: usize = width as usize;
+    let mut colors: Vec<Color3> = Vec::with_capacity(w * height as usize);
+    for _ in 0..height {
+        let head: &[u8] = bytes.get(pos..pos + 4).ok_or_else(|| invalid_data("HDR data is too short".to_string()))?;
+        let row: Vec<[u8; 4]> = if (8..=0x7fff).contains(&width) && head[0] == 2 && head[1] == 2 && head[2] & 0x80 == 0 {
+            if ((head[2] as usize) << 8 | head[3] as usize) != w {
+                return Err(invalid_data("HDR scanline width mismatch".to_string()));
+            }
+            pos += 4;
+            let components: Vec<Vec<u8>> = (0..4)
+                .map(|_| read_rle_component(bytes, &mut pos, w))
+                .collect::<io::Result<_>>()?;
+            (0..w).map(|x| [components[0][x], components[1][x], components[2][x], components[3][x]]).collect()
+        } else {
+            let data: &[u8] = bytes.get(pos..pos + 4 * w).ok_or_else(|| invalid_data("HDR data is too short".to_string()))?;
+            pos += 4 * w;
+            data.chunks(4).map(|p| [p[0], p[1], p[2], p[3]]).collect()
+        };
+        colors.extend(row.iter().map(|p| from_rgbe(p)));
+    }
+    Ok((width, height, colors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER_SIZE: usize = "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n".len();
+
+    fn encode(width: u32, height: u32, colors: &[Color3]) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        write_hdr(&mut bytes, width, height, colors).unwrap();
+        bytes
+    }
+
+    fn assert_round_trip(width: u32, height: u32, colors: &[Color3], bytes: &[u8]) {
+        let (w, h, decoded) = read_hdr(bytes).unwrap();
+        assert_eq!((w, h), (width, height));
+        assert_eq!(decoded.len(), colors.len());
+        for (a, b) in colors.iter().zip(decoded.iter()) {
+            // Components are quantized by steps of at most 1/128 of the largest one
+            let tolerance: f32 = a.r.max(a.g).max(a.b) / 128.0;
+            assert!((a.r - b.r).abs() <= tolerance, "{:?} != {:?}", a, b);
+            assert!((a.g - b.g).abs() <= tolerance, "{:?} != {:?}", a, b);
+            assert!((a.b - b.b).abs() <= tolerance, "{:?} != {:?}", a, b);
+        }
+    }
+
+    fn gradient(width: u32, height: u32) -> Vec<Color3> {
+        (0..width * height)
+            .map(|i| Color3 {r: i as f32 * 0.37, g: 1.0 / (i + 1) as f32, b: (i % 5) as f32 * 20.0})
+            .collect()
+    }
+
+    #[test]
+    fn round_trip_flat_scanlines() {
+        let colors: Vec<Color3> = gradient(5, 3);
+        let bytes: Vec<u8> = encode(5, 3, &colors);
+        let resolution: &[u8] = b"-Y 3 +X 5\n";
+        assert_eq!(bytes.len(), HEADER_SIZE + resolution.len() + 5 * 3 * 4);
+        assert_round_trip(5, 3, &colors, &bytes);
+    }
+
+    #[test]
+    fn round_trip_rle_scanlines() {
+        let colors: Vec<Color3> = gradient(37, 4);
+        let bytes: Vec<u8> = encode(37, 4, &colors);
+        let data: &[u8] = &bytes[HEADER_SIZE + b"-Y 4 +X 37\n".len()..];
+        assert_eq!(&data[..4], &[2, 2, 0, 37]);
+        assert_round_trip(37, 4, &colors, &bytes);
+    }
+
+    #[test]
+    fn round_trip_runs_longer_than_127() {
+        let width: u32 = 300;
+        let mut colors: Vec<Color3> = vec![Color3 {r: 0.25, g: 0.5, b: 2.0}; width as usize * 2];
+        // Break the second row into a run, some literal bytes and another run
+        for (i, c) in colors[width as usize + 130..width as usize + 140].iter_mut().enumerate() {
+            c.r = i as f32 * 0.1;
+        }
+        let bytes: Vec<u8> = encode(width, 2, &colors);
+        // A uniform row is three runs per component
+        let data: &[u8] = &bytes[HEADER_SIZE + b"-Y 2 +X 300\n".len()..];
+        assert_eq!(&data[..4], &[2, 2, 1, 44]);
+        assert_eq!(data[4], 128 + 127);
+        assert!(data.len() < 2 * width as usize);
+        assert_round_trip(width, 2, &colors, &bytes);
+    }
+
+    #[test]
+    fn reject_truncated_data() {
+        let colors: Vec<Color3> = gradient(37, 4);
+        let bytes: Vec<u8> = encode(37, 4, &colors);
+        assert!(read_hdr(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -1,26 +1,47 @@
+use std::sync::Arc;
 use rand;
 use rand::prelude::*;
 
 use crate::color3::Color3;
 use crate::vec3::Vec3;
 use crate::hitable::Hitable;
+use crate::bvh_hitable::BvhHitable;
 use crate::list_hitable::ListHitable;
 use crate::sphere_hitable::SphereHitable;
-use crate::material::{LambertMaterial, MetalMaterial};
+use crate::plane_hitable::PlaneHitable;
+use crate::moving_sphere_hitable::MovingSphereHitable;
+use crate::rect_hitable::{XyRectHitable, XzRectHitable, YzRectHitable};
+use crate::flip_normals_hitable::FlipNormalsHitable;
+use crate::box_hitable::BoxHitable;
+use crate::transform_hitable::TransformHitable;
+use crate::mat4::Mat4;
+use crate::material::{LambertMaterial, MetalMaterial, DiffuseLightMaterial};
+use crate::texture::ConstantTexture;
 use crate::camera::Camera;
 use crate::scene::Scene;
+use crate::background::{GradientBackground, SolidBackground};
 use crate::util;
 
 use crate::material::DielectricMaterial;
 
 // Book cover on the book of Ray Tracing in One Weekend
 pub fn iow_book_cover(rng: &mut rand::rngs::StdRng, width: u32, height: u32) -> Scene {
-    let mut hitables: Vec<Box<Hitable + Sync>> = Vec::new();
+    book_cover(rng, width, height, false)
+}
 
-    hitables.push(Box::new(SphereHitable {
-        center: Vec3{x: 0.0, y: -1000.0, z: 0.0},
-        radius: 1000.0,
-        material: Box::new(LambertMaterial{albedo: Color3{r: 0.5, g: 0.5, b: 0.5}})
+// Book cover at night, lit by glowing small balls and a lamp instead of the sky
+pub fn iow_book_cover_night(rng: &mut rand::rngs::StdRng, width: u32, height: u32) -> Scene {
+    book_cover(rng, width, height, true)
+}
+
+fn book_cover(rng: &mut rand::rngs::StdRng, width: u32, height: u32, night: bool) -> Scene {
+    let mut hitables: Vec<Box<dyn Hitable + Sync>> = Vec::new();
+    let mut lights: Vec<Box<dyn Hitable + Sync>> = Vec::new();
+
+    hitables.push(Box::new(PlaneHitable {
+        point: Vec3{x: 0.0, y: 0.0, z: 0.0},
+        normal: Vec3{x: 0.0, y: 1.0, z: 0.0},
+        material: Box::new(LambertMaterial{albedo: Box::new(ConstantTexture{color: Color3{r: 0.5, g: 0.5, b: 0.5}})})
     }));
 
     for a in -11..11 {
@@ -34,27 +55,36 @@ pub fn iow_book_cover(rng: &mut rand::rngs::StdRng, width: u32, height: u32) ->
 
             if (&center - &Vec3{x: 4.0, y: 0.2, z: 0.0}).length() > 0.9 {
                 if choose_mat < 0.8 {
-                    hitables.push(Box::new(SphereHitable {
-                        center,
-                        radius: 0.2,
-                        material: Box::new(LambertMaterial{
-                            albedo: Color3{
-                                r: rng.gen::<f32>() * rng.gen::<f32>(),
-                                g: rng.gen::<f32>() * rng.gen::<f32>(),
-                                b: rng.gen::<f32>() * rng.gen::<f32>()
-                            }
-                        })
-                    }));
+                    let albedo: Color3 = Color3{
+                        r: rng.gen::<f32>() * rng.gen::<f32>(),
+                        g: rng.gen::<f32>() * rng.gen::<f32>(),
+                        b: rng.gen::<f32>() * rng.gen::<f32>()
+                    };
+                    if night && choose_mat < 0.2 {
+                        let light: Arc<SphereHitable> = Arc::new(SphereHitable {
+                            center,
+                            radius: 0.2,
+                            material: Box::new(DiffuseLightMaterial{emit: &albedo * 8.0})
+                        });
+                        hitables.push(Box::new(light.clone()));
+                        lights.push(Box::new(light));
+                    } else {
+                        hitables.push(Box::new(SphereHitable {
+                            center,
+                            radius: 0.2,
+                            material: Box::new(LambertMaterial{albedo: Box::new(ConstantTexture{color: albedo})})
+                        }));
+                    }
                 } else if choose_mat < 0.95 {
                     hitables.push(Box::new(SphereHitable {
                         center,
                         radius: 0.2,
                         material: Box::new(MetalMaterial{
-                            albedo: Color3{
+                            albedo: Box::new(ConstantTexture{color: Color3{
                                 r: 0.5 * (1.0 + rng.gen::<f32>()),
                                 g: 0.5 * (1.0 + rng.gen::<f32>()),
                                 b: 0.5 * (1.0 + rng.gen::<f32>())
-                            },
+                            }}),
                             f: 0.5 * rng.gen::<f32>()
                         })
                     }));
@@ -62,7 +92,7 @@ pub fn iow_book_cover(rng: &mut rand::rngs::StdRng, width: u32, height: u32) ->
                     hitables.push(Box::new(SphereHitable {
                         center,
                         radius: 0.2,
-                        material: Box::new(DielectricMaterial{ref_idx: 1.5})
+                        material: Box::new(DielectricMaterial{ref_idx: 1.5, absorption: None, dispersion: None})
                     }));
                 }
             }
@@ -72,18 +102,28 @@ pub fn iow_book_cover(rng: &mut rand::rngs::StdRng, width: u32, height: u32) ->
     hitables.push(Box::new(SphereHitable {
         center: Vec3{x: 0.0, y: 1.0, z: 0.0},
         radius: 1.0,
-        material: Box::new(DielectricMaterial{ref_idx: 1.5})
+        material: Box::new(DielectricMaterial{ref_idx: 1.5, absorption: None, dispersion: None})
     }));
     hitables.push(Box::new(SphereHitable {
         center: Vec3{x: -4.0, y: 1.0, z: 0.0},
         radius: 1.0,
-        material: Box::new(LambertMaterial{albedo: Color3{r: 0.4, g: 0.2, b: 0.1}})
+        material: Box::new(LambertMaterial{albedo: Box::new(ConstantTexture{color: Color3{r: 0.4, g: 0.2, b: 0.1}})})
     }));
     hitables.push(Box::new(SphereHitable {
         center: Vec3{x: 4.0, y: 1.0, z: 0.0},
         radius: 1.0,
-        material: Box::new(MetalMaterial{albedo: Color3{r: 0.7, g: 0.6, b: 0.5}, f: 0.0})
+        material: Box::new(MetalMaterial{albedo: Box::new(ConstantTexture{color: Color3{r: 0.7, g: 0.6, b: 0.5}}), f: 0.0})
     }));
+    if night {
+        // lamp
+        let lamp: Arc<SphereHitable> = Arc::new(SphereHitable {
+            center: Vec3{x: 0.0, y: 6.0, z: 0.0},
+            radius: 1.5,
+            material: Box::new(DiffuseLightMaterial{emit: Color3{r: 4.0, g: 3.6, b: 3.0}})
+        });
+        hitables.push(Box::new(lamp.clone()));
+        lights.push(Box::new(lamp));
+    }
 
     let lookfrom: Vec3 = Vec3 {x: 13.0, y: 2.0, z: 3.0};
     let lookat  : Vec3 = Vec3 {x: 0.0, y: 0.0, z: 0.0};
@@ -96,12 +136,77 @@ pub fn iow_book_cover(rng: &mut rand::rngs::StdRng, width: u32, height: u32) ->
         vfov: 20.0,
         aspect: width as f32 / height as f32,
         aperture,
-        focus_dist
+        focus_dist,
+        time0: 0.0,
+        time1: 0.0
+    };
+
+    Scene {
+        camera,
+        hitable: Box::new(BvhHitable::new(hitables)),
+        lights: ListHitable {hitables: lights},
+        background: if night {
+            Box::new(SolidBackground {color: Color3 {r: 0.0, g: 0.0, b: 0.0}})
+        } else {
+            Box::new(GradientBackground::sky())
+        },
+        atmosphere: None
+    }
+}
+
+fn lambert(color: Color3) -> Box<LambertMaterial> {
+    Box::new(LambertMaterial{albedo: Box::new(ConstantTexture{color})})
+}
+
+// Box from the origin to the size, rotated around the Y axis by the angle in degrees and translated by the offset
+fn rotated_box(size: Vec3, degrees: f32, offset: Vec3, material: Box<LambertMaterial>) -> TransformHitable {
+    let matrix: Mat4 = &Mat4::translate(&offset) * &Mat4::rotate(&Vec3{x: 0.0, y: 1.0, z: 0.0}, degrees);
+    TransformHitable::new(Arc::new(BoxHitable::new(Vec3{x: 0.0, y: 0.0, z: 0.0}, size, material)), matrix).unwrap()
+}
+
+// Cornell box with two white boxes, lit by a light on the ceiling
+pub fn cornell_box(width: u32, height: u32) -> Scene {
+    let red  : Color3 = Color3{r: 0.65, g: 0.05, b: 0.05};
+    let white: Color3 = Color3{r: 0.73, g: 0.73, b: 0.73};
+    let green: Color3 = Color3{r: 0.12, g: 0.45, b: 0.15};
+    let light: Color3 = Color3{r: 15.0, g: 15.0, b: 15.0};
+
+    // Light facing down, shared with the lights
+    let light_hitable: Arc<FlipNormalsHitable> = Arc::new(FlipNormalsHitable {hitable: Box::new(XzRectHitable {
+        x0: 213.0, x1: 343.0, z0: 227.0, z1: 332.0, k: 554.0,
+        material: Box::new(DiffuseLightMaterial{emit: light})
+    })});
+    let hitables: Vec<Box<dyn Hitable + Sync>> = vec![
+        // Walls facing inwards
+        Box::new(FlipNormalsHitable {hitable: Box::new(YzRectHitable {y0: 0.0, y1: 555.0, z0: 0.0, z1: 555.0, k: 555.0, material: lambert(green)})}),
+        Box::new(YzRectHitable {y0: 0.0, y1: 555.0, z0: 0.0, z1: 555.0, k: 0.0, material: lambert(red)}),
+        Box::new(light_hitable.clone()),
+        Box::new(FlipNormalsHitable {hitable: Box::new(XzRectHitable {x0: 0.0, x1: 555.0, z0: 0.0, z1: 555.0, k: 555.0, material: lambert(white)})}),
+        Box::new(XzRectHitable {x0: 0.0, x1: 555.0, z0: 0.0, z1: 555.0, k: 0.0, material: lambert(white)}),
+        Box::new(FlipNormalsHitable {hitable: Box::new(XyRectHitable {x0: 0.0, x1: 555.0, y0: 0.0, y1: 555.0, k: 555.0, material: lambert(white)})}),
+        Box::new(rotated_box(Vec3{x: 165.0, y: 330.0, z: 165.0}, 15.0, Vec3{x: 265.0, y: 0.0, z: 295.0}, lambert(white))),
+        Box::new(rotated_box(Vec3{x: 165.0, y: 165.0, z: 165.0}, -18.0, Vec3{x: 130.0, y: 0.0, z: 65.0}, lambert(white)))
+    ];
+    let lights: Vec<Box<dyn Hitable + Sync>> = vec![Box::new(light_hitable)];
+
+    let camera: Camera = Camera {
+        lookfrom: Vec3 {x: 278.0, y: 278.0, z: -800.0},
+        lookat: Vec3 {x: 278.0, y: 278.0, z: 0.0},
+        vup: Vec3 {x: 0.0, y: 1.0, z: 0.0},
+        vfov: 40.0,
+        aspect: width as f32 / height as f32,
+        aperture: 0.0,
+        focus_dist: 10.0,
+        time0: 0.0,
+        time1: 0.0
     };
 
     Scene {
         camera,
-        hitable: Box::new(ListHitable{hitables})
+        hitable: Box::new(BvhHitable::new(hitables)),
+        lights: ListHitable {hitables: lights},
+        background: Box::new(SolidBackground {color: Color3 {r: 0.0, g: 0.0, b: 0.0}}),
+        atmosphere: None
     }
 }
 
@@ -111,6 +216,8 @@ pub struct FreeFallAnimation {
     dt: f32,
     min_t: f32,
     max_t: f32,
+    // How long the shutter is open in each frame
+    shutter: f32,
 
     // passed time
     t: f32,
@@ -123,11 +230,12 @@ struct MovingHitableGenerator {
     k: f32,
     v: f32,
     y: f32,
-    sphere_hitable: Box<dyn Fn(f32) -> Box<dyn Hitable + Sync>>
+    // Sphere moving from the first height at time 0 to the second height at the given time
+    sphere_hitable: Box<dyn Fn(f32, f32, f32) -> Box<dyn Hitable + Sync>>
 }
 
 impl FreeFallAnimation {
-    pub fn new(width: u32, height: u32, dt: f32, min_t: f32, max_t: f32, random_seed: u8) -> FreeFallAnimation {
+    pub fn new(width: u32, height: u32, dt: f32, min_t: f32, max_t: f32, shutter: f32, random_seed: u64) -> FreeFallAnimation {
         let mut rng = util::rng_by_seed(random_seed);
 
         let moving_hitable_generators = {
@@ -180,11 +288,14 @@ impl FreeFallAnimation {
                                 k: 0.6,
                                 v: 10.0 + (4.0 * rng.gen::<f32>() - 2.0),
                                 y: Self::SMALL_SPHERE_RADIUS,
-                                sphere_hitable: Box::new(move |y| Box::new(SphereHitable {
-                                    center: Vec3 {x, y, z},
+                                sphere_hitable: Box::new(move |y0, y1, time1| Box::new(MovingSphereHitable {
+                                    center0: Vec3 {x, y: y0, z},
+                                    center1: Vec3 {x, y: y1, z},
+                                    time0: 0.0,
+                                    time1,
                                     radius: Self::SMALL_SPHERE_RADIUS,
                                     material: Box::new(LambertMaterial{
-                                        albedo
+                                        albedo: Box::new(ConstantTexture{color: albedo})
                                     })
                                 }))
                             });
@@ -200,11 +311,14 @@ impl FreeFallAnimation {
                                 k: 0.5,
                                 v: 10.0 + (4.0 * rng.gen::<f32>() - 2.0),
                                 y: Self::SMALL_SPHERE_RADIUS,
-                                sphere_hitable: Box::new(move |y| Box::new(SphereHitable {
-                                    center: Vec3 {x, y, z},
+                                sphere_hitable: Box::new(move |y0, y1, time1| Box::new(MovingSphereHitable {
+                                    center0: Vec3 {x, y: y0, z},
+                                    center1: Vec3 {x, y: y1, z},
+                                    time0: 0.0,
+                                    time1,
                                     radius: Self::SMALL_SPHERE_RADIUS,
                                     material: Box::new(MetalMaterial {
-                                        albedo,
+                                        albedo: Box::new(ConstantTexture{color: albedo}),
                                         f,
                                     })
                                 }))
@@ -215,10 +329,13 @@ impl FreeFallAnimation {
                                 k: 0.5,
                                 v: 10.0 + (4.0 * rng.gen::<f32>() - 2.0),
                                 y: Self::SMALL_SPHERE_RADIUS,
-                                sphere_hitable: Box::new(move |y| Box::new(SphereHitable {
-                                    center: Vec3 {x, y, z},
+                                sphere_hitable: Box::new(move |y0, y1, time1| Box::new(MovingSphereHitable {
+                                    center0: Vec3 {x, y: y0, z},
+                                    center1: Vec3 {x, y: y1, z},
+                                    time0: 0.0,
+                                    time1,
                                     radius: Self::SMALL_SPHERE_RADIUS,
-                                    material: Box::new(DielectricMaterial{ref_idx: 1.5})
+                                    material: Box::new(DielectricMaterial{ref_idx: 1.5, absorption: None, dispersion: None})
                                 }))
                             });
                         }
@@ -237,6 +354,7 @@ impl FreeFallAnimation {
             dt,
             min_t,
             max_t,
+            shutter,
             t: 0.0,
             look_from_theta: 2.0 * std::f32::consts::PI,
             moving_hitable_generators
@@ -260,15 +378,34 @@ impl FreeFallAnimation {
     fn physical_update(&mut self) {
         self.t += self.dt;
         for hitable_generator in &mut self.moving_hitable_generators {
-            let f = - hitable_generator.m * Self::G;
-            if hitable_generator.v < 0.0 && hitable_generator.y < Self::SMALL_SPHERE_RADIUS {
-                hitable_generator.v = -hitable_generator.k * hitable_generator.v;
-            } else {
-                let a = f / hitable_generator.m;
-                hitable_generator.v += a * self.dt;
-            }
-            hitable_generator.y += hitable_generator.v * self.dt;
+            let (v, y) = Self::fall(hitable_generator, hitable_generator.v, hitable_generator.y, self.dt);
+            hitable_generator.v = v;
+            hitable_generator.y = y;
+        }
+    }
+
+    // Velocity and height after dt from the given velocity and height
+    fn fall(hitable_generator: &MovingHitableGenerator, v: f32, y: f32, dt: f32) -> (f32, f32) {
+        let f = - hitable_generator.m * Self::G;
+        let v = if v < 0.0 && y < Self::SMALL_SPHERE_RADIUS {
+            -hitable_generator.k * v
+        } else {
+            let a = f / hitable_generator.m;
+            v + a * dt
+        };
+        (v, y + v * dt)
+    }
+
+    // Height when the shutter closes, simulated ahead by the same steps as the animation
+    fn shutter_close_y(&self, hitable_generator: &MovingHitableGenerator, n_steps: usize) -> f32 {
+        let mut v: f32 = hitable_generator.v;
+        let mut y: f32 = hitable_generator.y;
+        for _ in 0..n_steps {
+            let (next_v, next_y) = Self::fall(hitable_generator, v, y, self.dt);
+            v = next_v;
+            y = next_y;
         }
+        y
     }
 }
 
@@ -286,6 +423,9 @@ impl Iterator for FreeFallAnimation {
         let hitable = if self.t > self.max_t {
             None
         } else {
+            // Time is relative to the frame, and the shutter is open for whole steps of the simulation
+            let n_shutter_steps: usize = (self.shutter / self.dt).round() as usize;
+            let shutter_time: f32 = n_shutter_steps as f32 * self.dt;
             let camera: Camera = {
                 let r: f32 = 200.0f32.sqrt();
                 let lookfrom: Vec3 = Vec3 {
@@ -303,39 +443,46 @@ impl Iterator for FreeFallAnimation {
                     vfov: 20.0,
                     aspect: self.width as f32 / self.height as f32,
                     aperture,
-                    focus_dist
+                    focus_dist,
+                    time0: 0.0,
+                    time1: shutter_time
                 }
             };
 
 
-            let hitables: Vec<Box<dyn Hitable + Sync>> = vec![
+            let mut hitables: Vec<Box<dyn Hitable + Sync>> = vec![
                 // ground
-                Box::new(SphereHitable {
-                    center: Vec3{x: 0.0, y: -1000.0, z: 0.0},
-                    radius: 1000.0,
-                    material: Box::new(LambertMaterial{albedo: Color3{r: 0.5, g: 0.5, b: 0.5}})
+                Box::new(PlaneHitable {
+                    point: Vec3{x: 0.0, y: 0.0, z: 0.0},
+                    normal: Vec3{x: 0.0, y: 1.0, z: 0.0},
+                    material: Box::new(LambertMaterial{albedo: Box::new(ConstantTexture{color: Color3{r: 0.5, g: 0.5, b: 0.5}})})
                 }),
-                // small balls
-                Box::new(ListHitable{hitables: self.moving_hitable_generators.iter().map(|g| (g.sphere_hitable)(g.y)).collect()}),
                 Box::new(SphereHitable {
                     center: Vec3{x: 0.0, y: 1.0, z: 0.0},
                     radius: 1.0,
-                    material: Box::new(DielectricMaterial{ref_idx: 1.5})
+                    material: Box::new(DielectricMaterial{ref_idx: 1.5, absorption: None, dispersion: None})
                 }),
                 Box::new(SphereHitable {
                     center: Vec3{x: -4.0, y: 1.0, z: 0.0},
                     radius: 1.0,
-                    material: Box::new(LambertMaterial{albedo: Color3{r: 0.4, g: 0.2, b: 0.1}})
+                    material: Box::new(LambertMaterial{albedo: Box::new(ConstantTexture{color: Color3{r: 0.4, g: 0.2, b: 0.1}})})
                 }),
                 Box::new(SphereHitable {
                     center: Vec3{x: 4.0, y: 1.0, z: 0.0},
                     radius: 1.0,
-                    material: Box::new(MetalMaterial{albedo: Color3{r: 0.7, g: 0.6, b: 0.5}, f: 0.0})
+                    material: Box::new(MetalMaterial{albedo: Box::new(ConstantTexture{color: Color3{r: 0.7, g: 0.6, b: 0.5}}), f: 0.0})
                 })
             ];
+            // small balls
+            hitables.extend(self.moving_hitable_generators.iter().map(|g|
+                (g.sphere_hitable)(g.y, self.shutter_close_y(g, n_shutter_steps), shutter_time)
+            ));
             Some(Scene {
                 camera,
-                hitable: Box::new(ListHitable{ hitables })
+                hitable: Box::new(BvhHitable::new(hitables)),
+                lights: ListHitable {hitables: vec![]},
+                background: Box::new(GradientBackground::sky()),
+                atmosphere: None
             })
         };
 
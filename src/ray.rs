@@ -2,7 +2,11 @@ use crate::vec3::Vec3;
 
 pub struct Ray {
     pub origin: Vec3,
-    pub direction: Vec3
+    pub direction: Vec3,
+    // Time when the ray is shot, which moving objects depend on
+    pub time: f32,
+    // Wavelength in nanometres carried by the path in spectral rendering (None for RGB)
+    pub wavelength: Option<f32>
 }
 
 impl Ray {
@@ -0,0 +1,197 @@
+use rand::prelude::*;
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::hitable::Hitable;
+use crate::hitable::HitRecord;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use core::borrow::Borrow;
+
+// Rectangle perpendicular to the axis `c` at `k`, spanning [a0, a1] and [b0, b1] on the other axes `a` and `b`
+pub struct AxisAlignedRect {
+    pub axes: (usize, usize, usize),
+    pub a0: f32,
+    pub a1: f32,
+    pub b0: f32,
+    pub b1: f32,
+    pub k: f32
+}
+
+impl AxisAlignedRect {
+    // Point on the plane of the rectangle
+    fn point(&self, a: f32, b: f32) -> Vec3 {
+        let (axis_a, axis_b, _) = self.axes;
+        let mut values: [f32; 3] = [self.k; 3];
+        values[axis_a] = a;
+        values[axis_b] = b;
+        Vec3 {x: values[0], y: values[1], z: values[2]}
+    }
+
+    // Unit normal in the positive direction of the axis `c`
+    pub fn normal(&self) -> Vec3 {
+        let (_, _, axis_c) = self.axes;
+        let mut values: [f32; 3] = [0.0; 3];
+        values[axis_c] = 1.0;
+        Vec3 {x: values[0], y: values[1], z: values[2]}
+    }
+
+    // t and surface coordinates of the intersection
+    pub fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<(f32, f32, f32)> {
+        let (axis_a, axis_b, axis_c) = self.axes;
+        let t: f32 = (self.k - r.origin[axis_c]) / r.direction[axis_c];
+        // NOTE: Written in this way so that NaN (rays parallel to the rectangle) is rejected
+        if !(t_min < t && t < t_max) {
+            return None;
+        }
+        let a: f32 = r.origin[axis_a] + t * r.direction[axis_a];
+        let b: f32 = r.origin[axis_b] + t * r.direction[axis_b];
+        if a < self.a0 || a > self.a1 || b < self.b0 || b > self.b1 {
+            return None;
+        }
+        Some((t, (a - self.a0) / (self.a1 - self.a0), (b - self.b0) / (self.b1 - self.b0)))
+    }
+
+    pub fn bounding_box(&self) -> Aabb {
+        // NOTE: Padded so that the box does not have zero thickness
+        let pad: Vec3 = &self.normal() * 1e-4;
+        Aabb {
+            min: &self.point(self.a0, self.b0) - &pad,
+            max: &self.point(self.a1, self.b1) + &pad
+        }
+    }
+
+    // Points are sampled uniformly on the rectangle, converted into the density per solid angle
+    pub fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        let r: Ray = Ray {origin: *origin, direction: *direction, time: 0.0, wavelength: None};
+        match self.hit(&r, 0.0, f32::MAX) {
+            Some((t, _, _)) => {
+                let (_, _, axis_c) = self.axes;
+                let area: f32 = (self.a1 - self.a0) * (self.b1 - self.b0);
+                let dist_squared: f32 = t * t * direction.squared_length();
+                let cosine: f32 = (direction[axis_c] / direction.length()).abs();
+                dist_squared / (cosine * area)
+            },
+            None => 0.0
+        }
+    }
+
+    pub fn random(&self, rng: &mut rand::rngs::StdRng, origin: &Vec3) -> Vec3 {
+        let a: f32 = self.a0 + rng.gen::<f32>() * (self.a1 - self.a0);
+        let b: f32 = self.b0 + rng.gen::<f32>() * (self.b1 - self.b0);
+        &self.point(a, b) - origin
+    }
+}
+
+fn hit_rect<'a>(rect: &AxisAlignedRect, material: &'a (dyn Material + Sync), r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'a>> {
+    let (t, u, v) = rect.hit(r, t_min, t_max)?;
+    Some(HitRecord {
+        t,
+        p: r.point_at_parameter(t),
+        normal: rect.normal(),
+        u,
+        v,
+        material
+    })
+}
+
+// Rectangle in the XY plane at z = k, facing +Z
+pub struct XyRectHitable {
+    pub x0: f32,
+    pub x1: f32,
+    pub y0: f32,
+    pub y1: f32,
+    pub k: f32,
+    pub material: Box<dyn Material + std::marker::Sync>
+}
+
+impl XyRectHitable {
+    fn rect(&self) -> AxisAlignedRect {
+        AxisAlignedRect {axes: (0, 1, 2), a0: self.x0, a1: self.x1, b0: self.y0, b1: self.y1, k: self.k}
+    }
+}
+
+impl Hitable for XyRectHitable {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        hit_rect(&self.rect(), self.material.borrow(), r, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.rect().bounding_box())
+    }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        self.rect().pdf_value(origin, direction)
+    }
+
+    fn random(&self, rng: &mut rand::rngs::StdRng, origin: &Vec3) -> Vec3 {
+        self.rect().random(rng, origin)
+    }
+}
+
+// Rectangle in the XZ plane at y = k, facing +Y
+pub struct XzRectHitable {
+    pub x0: f32,
+    pub x1: f32,
+    pub z0: f32,
+    pub z1: f32,
+    pub k: f32,
+    pub material: Box<dyn Material + std::marker::Sync>
+}
+
+impl XzRectHitable {
+    fn rect(&self) -> AxisAlignedRect {
+        AxisAlignedRect {axes: (0, 2, 1), a0: self.x0, a1: self.x1, b0: self.z0, b1: self.z1, k: self.k}
+    }
+}
+
+impl Hitable for XzRectHitable {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        hit_rect(&self.rect(), self.material.borrow(), r, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.rect().bounding_box())
+    }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        self.rect().pdf_value(origin, direction)
+    }
+
+    fn random(&self, rng: &mut rand::rngs::StdRng, origin: &Vec3) -> Vec3 {
+        self.rect().random(rng, origin)
+    }
+}
+
+// Rectangle in the YZ plane at x = k, facing +X
+pub struct YzRectHitable {
+    pub y0: f32,
+    pub y1: f32,
+    pub z0: f32,
+    pub z1: f32,
+    pub k: f32,
+    pub material: Box<dyn Material + std::marker::Sync>
+}
+
+impl YzRectHitable {
+    fn rect(&self) -> AxisAlignedRect {
+        AxisAlignedRect {axes: (1, 2, 0), a0: self.y0, a1: self.y1, b0: self.z0, b1: self.z1, k: self.k}
+    }
+}
+
+impl Hitable for YzRectHitable {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        hit_rect(&self.rect(), self.material.borrow(), r, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.rect().bounding_box())
+    }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        self.rect().pdf_value(origin, direction)
+    }
+
+    fn random(&self, rng: &mut rand::rngs::StdRng, origin: &Vec3) -> Vec3 {
+        self.rect().random(rng, origin)
+    }
+}
@@ -1,117 +1,478 @@
 use std::io;
 use std::fs;
 use std::path;
-use std::io::Write;
+use std::ops::RangeInclusive;
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
 use rand::prelude::*;
 use rayon::prelude::*;
+use serde::{Serialize, Deserialize};
 
 use crate::color3::Color3;
 use crate::vec3::Vec3;
 use crate::ray::Ray;
-use crate::hitable::Hitable;
+use crate::hitable::{Hitable, HitRecord};
 use crate::util;
-use core::borrow::{BorrowMut, Borrow};
+use crate::spectrum;
+use core::borrow::BorrowMut;
 use crate::scene::Scene;
 use crate::camera::Camera;
+use crate::image_format::{self, ImageFormat};
 
-fn color(rng: &mut rand::rngs::StdRng, r: &Ray, hitable: &Hitable, min_float: f32, depth: i32) -> Color3 {
-    if let Some(hit_record) = hitable.hit(r, min_float, std::f32::MAX) {
+// Weight of a sample by the balance heuristic of multiple importance sampling
+fn mis_weight(pdf: f32, other_pdf: f32) -> f32 {
+    pdf / (pdf + other_pdf)
+}
+
+fn is_black(c: &Color3) -> bool {
+    c.r <= 0.0 && c.g <= 0.0 && c.b <= 0.0
+}
+
+// Light from a direction sampled towards the lights (next event estimation)
+fn sample_lights(rng: &mut rand::rngs::StdRng, r: &Ray, hit_record: &HitRecord, scene: &Scene, min_float: f32) -> Color3 {
+    let black: Color3 = Color3 {r: 0.0, g: 0.0, b: 0.0};
+    if scene.lights.hitables.is_empty() {
+        return black;
+    }
+    let direction: Vec3 = scene.lights.random(rng, &hit_record.p);
+    let light_pdf: f32 = scene.lights.pdf_value(&hit_record.p, &direction);
+    let (value, scatter_pdf) = hit_record.material.scattering(r, hit_record, &direction);
+    if light_pdf <= 0.0 || scatter_pdf <= 0.0 {
+        return black;
+    }
+    let shadow_ray: Ray = Ray {origin: hit_record.p, direction, time: r.time, wavelength: r.wavelength};
+    match scene.hitable.hit(&shadow_ray, min_float, f32::MAX) {
+        Some(light_hit_record) => {
+            let emitted: Color3 = spectrum::at_wavelength(&light_hit_record.material.emitted(&light_hit_record), r.wavelength);
+            let value: Color3 = spectrum::at_wavelength(&value, r.wavelength);
+            let transmittance: f32 = match &scene.atmosphere {
+                Some(atmosphere) => atmosphere.transmittance(light_hit_record.t * direction.length()),
+                None => 1.0
+            };
+            &(&emitted * &value) * (transmittance * mis_weight(light_pdf, scatter_pdf) / light_pdf)
+        },
+        None => black
+    }
+}
+
+// Light from a direction sampled towards the background such as an environment map
+// NOTE: Light escaping the scene and light from emissive hitables are weighted against scattering separately
+fn sample_background(rng: &mut rand::rngs::StdRng, r: &Ray, hit_record: &HitRecord, scene: &Scene, min_float: f32) -> Color3 {
+    let black: Color3 = Color3 {r: 0.0, g: 0.0, b: 0.0};
+    // NOTE: No light from the background gets through the atmosphere filling the whole scene
+    if scene.atmosphere.is_some() {
+        return black;
+    }
+    let direction: Vec3 = match scene.background.random(rng) {
+        Some(direction) => direction,
+        None => return black
+    };
+    let background_pdf: f32 = scene.background.pdf_value(&direction);
+    let (value, scatter_pdf) = hit_record.material.scattering(r, hit_record, &direction);
+    if background_pdf <= 0.0 || scatter_pdf <= 0.0 {
+        return black;
+    }
+    let shadow_ray: Ray = Ray {origin: hit_record.p, direction, time: r.time, wavelength: r.wavelength};
+    if scene.hitable.hit(&shadow_ray, min_float, f32::MAX).is_some() {
+        return black;
+    }
+    let background: Color3 = spectrum::at_wavelength(&scene.background.color(&direction), r.wavelength);
+    let value: Color3 = spectrum::at_wavelength(&value, r.wavelength);
+    &(&background * &value) * (mis_weight(background_pdf, scatter_pdf) / background_pdf)
+}
+
+// `scatter_pdf` is the density of the material choosing the direction of the ray,
+// which weights emission hit by the ray against light sampling (None for camera rays and specular scattering)
+// NOTE: Colors are taken at the wavelength of the ray in spectral rendering
+fn color(rng: &mut rand::rngs::StdRng, r: &Ray, scene: &Scene, min_float: f32, depth: i32, scatter_pdf: Option<f32>) -> Color3 {
+    let mut hit_record_opt: Option<HitRecord> = scene.hitable.hit(r, min_float, f32::MAX);
+    // Scatter in the atmosphere before hitting an object
+    if let Some(atmosphere) = &scene.atmosphere {
+        let t_max: f32 = hit_record_opt.as_ref().map_or(f32::MAX, |hit_record| hit_record.t);
+        if let Some(hit_record) = atmosphere.hit(rng, r, t_max) {
+            hit_record_opt = Some(hit_record);
+        }
+    }
+    if let Some(hit_record) = hit_record_opt {
+        let mut emitted: Color3 = spectrum::at_wavelength(&hit_record.material.emitted(&hit_record), r.wavelength);
+        if let Some(scatter_pdf) = scatter_pdf {
+            if !is_black(&emitted) && !scene.lights.hitables.is_empty() {
+                let light_pdf: f32 = scene.lights.pdf_value(&r.origin, &r.direction);
+                emitted = &emitted * mis_weight(scatter_pdf, light_pdf);
+            }
+        }
         if depth < 50 {
             if let Some(scatter_record) = hit_record.material.scatter(rng, r, &hit_record) {
-                let col = color(rng, &scatter_record.scattered, hitable, min_float, depth+1);
-                let attenuation = scatter_record.attenuation;
-                Color3 {
-                    r: col.r * attenuation.r,
-                    g: col.g * attenuation.g,
-                    b: col.b * attenuation.b
-                }
+                let direct: Color3 = match scatter_record.pdf {
+                    Some(_) => &sample_lights(rng, r, &hit_record, scene, min_float) + &sample_background(rng, r, &hit_record, scene, min_float),
+                    None => Color3 {r: 0.0, g: 0.0, b: 0.0}
+                };
+                let col = color(rng, &scatter_record.scattered, scene, min_float, depth+1, scatter_record.pdf);
+                &(&emitted + &direct) + &(&col * &spectrum::at_wavelength(&scatter_record.attenuation, r.wavelength))
             } else {
-                Color3 {r: 0.0, g: 0.0, b: 0.0}
+                emitted
             }
         } else {
-            Color3 {r: 0.0, g: 0.0, b: 0.0}
+            emitted
         }
     } else {
-        let unit_direction : Vec3 = r.direction.unit_vector();
-        let t              : f32  = 0.5 * (unit_direction.y + 1.0);
-        &(&Color3 { r: 1.0, g: 1.0, b: 1.0 } * (1.0 - t)) + &(&Color3 {r: 0.5, g: 0.7, b: 1.0} * t)
+        let mut background: Color3 = spectrum::at_wavelength(&scene.background.color(&r.direction), r.wavelength);
+        if let Some(scatter_pdf) = scatter_pdf {
+            let background_pdf: f32 = scene.background.pdf_value(&r.direction);
+            if background_pdf > 0.0 {
+                background = &background * mis_weight(scatter_pdf, background_pdf);
+            }
+        }
+        background
+    }
+}
+
+// Settings shared by image and animation rendering
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RenderSettings {
+    pub width: u32,
+    pub height: u32,
+    pub n_samples: u32,
+    pub min_float: f32,
+    pub random_seed: u64,
+    // Keep sampling noisy pixels after `n_samples` (None to take exactly `n_samples`)
+    pub adaptive: Option<AdaptiveSampling>,
+    // Size of square tiles rendered in parallel
+    pub tile_size: u32,
+    // Number of samples per pixel in each pass over the image
+    pub samples_per_pass: u32,
+    // Interval to write the image in progress into the output file
+    pub preview_interval: Option<Duration>,
+    // Trace a wavelength per path instead of RGB
+    pub spectral: bool
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AdaptiveSampling {
+    // Sampling of a pixel stops when the standard error of its mean luminance is below this ratio of the mean
+    pub threshold: f32,
+    pub max_samples: u32
+}
+
+impl RenderSettings {
+    fn max_samples(&self) -> u32 {
+        match &self.adaptive {
+            Some(adaptive) => adaptive.max_samples.max(self.n_samples),
+            None => self.n_samples
+        }
+    }
+}
+
+// Samples accumulated in a pixel with the running mean and variance of luminance (Welford's algorithm)
+#[derive(Clone)]
+pub struct PixelAccumulator {
+    pub sum: Color3,
+    pub n: u32,
+    pub mean: f64,
+    pub m2: f64
+}
+
+impl PixelAccumulator {
+    // NOTE: Nearly black pixels are compared with this luminance so that they do not need endless samples
+    const MIN_LUMINANCE: f64 = 0.01;
+    // Size in bytes of an encoded pixel: sum (3 x f32), number of samples (u32), mean and M2 (f64)
+    pub const ENCODED_SIZE: usize = 32;
+
+    // Write the pixel in little endian so that it is restored exactly
+    pub fn write_le<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.sum.r.to_le_bytes())?;
+        writer.write_all(&self.sum.g.to_le_bytes())?;
+        writer.write_all(&self.sum.b.to_le_bytes())?;
+        writer.write_all(&self.n.to_le_bytes())?;
+        writer.write_all(&self.mean.to_le_bytes())?;
+        writer.write_all(&self.m2.to_le_bytes())
+    }
+
+    pub fn read_le<R: Read>(reader: &mut R) -> io::Result<PixelAccumulator> {
+        let mut b: [u8; PixelAccumulator::ENCODED_SIZE] = [0; PixelAccumulator::ENCODED_SIZE];
+        reader.read_exact(&mut b)?;
+        let f32_at = |i: usize| f32::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]);
+        let f64_at = |i: usize| f64::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3], b[i + 4], b[i + 5], b[i + 6], b[i + 7]]);
+        Ok(PixelAccumulator {
+            sum: Color3 {r: f32_at(0), g: f32_at(4), b: f32_at(8)},
+            n: u32::from_le_bytes([b[12], b[13], b[14], b[15]]),
+            mean: f64_at(16),
+            m2: f64_at(24)
+        })
+    }
+
+    fn new() -> PixelAccumulator {
+        PixelAccumulator {sum: Color3 {r: 0.0, g: 0.0, b: 0.0}, n: 0, mean: 0.0, m2: 0.0}
+    }
+
+    fn add(&mut self, c: &Color3) {
+        self.sum = &self.sum + c;
+        let y: f64 = (0.2126 * c.r + 0.7152 * c.g + 0.0722 * c.b) as f64;
+        self.n += 1;
+        let delta: f64 = y - self.mean;
+        self.mean += delta / self.n as f64;
+        self.m2 += delta * (y - self.mean);
+    }
+
+    fn converged(&self, threshold: f32) -> bool {
+        if self.n < 2 {
+            return false;
+        }
+        let variance: f64 = self.m2 / (self.n - 1) as f64;
+        let standard_error: f64 = (variance / self.n as f64).sqrt();
+        standard_error <= threshold as f64 * self.mean.max(Self::MIN_LUMINANCE)
+    }
+
+    fn color(&self) -> Color3 {
+        if self.n == 0 {
+            Color3 {r: 0.0, g: 0.0, b: 0.0}
+        } else {
+            &self.sum / self.n as f32
+        }
+    }
+
+    // Whether the pixel needs no more samples
+    fn is_finished(&self, settings: &RenderSettings) -> bool {
+        if self.n >= settings.max_samples() {
+            return true;
+        }
+        match &settings.adaptive {
+            Some(adaptive) => self.n >= settings.n_samples && self.converged(adaptive.threshold),
+            None => false
+        }
+    }
+}
+
+// Accumulation buffer of progressive rendering (pixels in rows from the top-left corner)
+pub struct Accumulation {
+    pub pixels: Vec<PixelAccumulator>
+}
+
+impl Accumulation {
+    pub fn new(settings: &RenderSettings) -> Accumulation {
+        Accumulation {
+            pixels: vec![PixelAccumulator::new(); (settings.width * settings.height) as usize]
+        }
+    }
+
+    // Current image
+    pub fn image(&self) -> Vec<Color3> {
+        self.pixels.iter().map(|p| p.color()).collect()
+    }
+
+    pub fn sample_counts(&self) -> Vec<u32> {
+        self.pixels.iter().map(|p| p.n).collect()
     }
 }
 
-pub fn render<W: Write>(mut writer: io::BufWriter<W>, random_seed: u8, scene: &Scene, width: u32, height: u32, n_samples: u32, min_float: f32) {
-    let mut rng = util::rng_by_seed(random_seed);
+// Rectangle of pixels rendered as a unit (the position is from the top-left corner)
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32
+}
 
-    let nx: u32 = width;
-    let ny: u32 = height;
-    let ns: u32 = n_samples;
-    writer.write_all(format!("P3\n{} {}\n255\n", nx, ny).as_bytes()).unwrap();
+impl Tile {
+    // Pixel indices in the image in row-major order
+    pub fn pixel_indices(&self, image_width: u32) -> impl Iterator<Item=usize> + '_ {
+        (self.y..self.y + self.height)
+            .flat_map(move |y| (self.x..self.x + self.width).map(move |x| (y * image_width + x) as usize))
+    }
+}
 
+pub fn tiles(width: u32, height: u32, tile_size: u32) -> Vec<Tile> {
+    let mut tiles: Vec<Tile> = Vec::new();
+    for y in (0..height).step_by(tile_size as usize) {
+        for x in (0..width).step_by(tile_size as usize) {
+            tiles.push(Tile {x, y, width: tile_size.min(width - x), height: tile_size.min(height - y)});
+        }
+    }
+    tiles
+}
+
+// Take up to `n` more samples of the pixel at (x, y) from the top-left corner
+// (`frame` is the index of the animation frame)
+fn sample_pixel(scene: &Scene, settings: &RenderSettings, frame: u64, x: u32, y: u32, accumulator: &mut PixelAccumulator, n: u32) {
     let camera: &Camera = &scene.camera;
-    let h: &(Hitable + Sync) = scene.hitable.borrow();
-
-    // Position and seed pairs
-    let pos_and_seeds: Vec<((u32, u32), u8)> = {
-        // NOTE: This random seed generation should be evaluated strictly for reproducible random
-        let mut v: Vec<((u32, u32), u8)> = Vec::new();
-        for j in (0..ny).rev() {
-            for i in 0..nx {
-                let seed: u8 = rng.gen();
-                v.push(((i, j), seed));
+    let pixel: u64 = y as u64 * settings.width as u64 + x as u64;
+    // Counted from the bottom
+    let j: u32 = settings.height - 1 - y;
+    // NOTE: Samples are added in order for the same sum regardless of scheduling and passes
+    for _ in 0..n {
+        if accumulator.is_finished(settings) {
+            break;
+        }
+        let mut sample_rng = util::sample_rng(settings.random_seed, frame, pixel, accumulator.n as u64);
+        let u: f32 = (x as f32 + sample_rng.gen::<f32>()) / settings.width as f32;
+        let v: f32 = (j as f32 + sample_rng.gen::<f32>()) / settings.height as f32;
+        let r: Ray = camera.get_ray(&mut sample_rng, u, v);
+        let c: Color3 = if settings.spectral {
+            let wavelength: f32 = spectrum::sample_wavelength(sample_rng.gen());
+            let r: Ray = Ray {wavelength: Some(wavelength), ..r};
+            // NOTE: Radiance of a single wavelength is gray
+            let radiance: Color3 = color(sample_rng.borrow_mut(), &r, scene, settings.min_float, 0, None);
+            spectrum::to_rgb(radiance.g, wavelength)
+        } else {
+            color(sample_rng.borrow_mut(), &r, scene, settings.min_float, 0, None)
+        };
+        accumulator.add(&c);
+    }
+}
+
+// Render one pass of the tile and return its pixels in row-major order
+pub fn render_tile_pass(scene: &Scene, settings: &RenderSettings, frame: u64, tile: &Tile, accumulation: &Accumulation) -> Vec<PixelAccumulator> {
+    let mut pixels: Vec<PixelAccumulator> = Vec::with_capacity((tile.width * tile.height) as usize);
+    for (idx, pixel_idx) in tile.pixel_indices(settings.width).enumerate() {
+        let mut accumulator: PixelAccumulator = accumulation.pixels[pixel_idx].clone();
+        let x: u32 = tile.x + idx as u32 % tile.width;
+        let y: u32 = tile.y + idx as u32 / tile.width;
+        sample_pixel(scene, settings, frame, x, y, &mut accumulator, settings.samples_per_pass);
+        pixels.push(accumulator);
+    }
+    pixels
+}
+
+// Render the tile until its pixels are finished, sampling the pixels in parallel, and return them in row-major order
+pub fn render_tile(scene: &Scene, settings: &RenderSettings, frame: u64, tile: &Tile) -> Vec<PixelAccumulator> {
+    (0..tile.width * tile.height).into_par_iter().map(|idx| {
+        let mut accumulator: PixelAccumulator = PixelAccumulator::new();
+        sample_pixel(scene, settings, frame, tile.x + idx % tile.width, tile.y + idx / tile.width, &mut accumulator, settings.max_samples());
+        accumulator
+    }).collect()
+}
+
+// Render passes over tiles in parallel until all pixels are finished, calling `after_pass` after each pass
+pub fn render_passes<F: FnMut(&Accumulation)>(scene: &Scene, frame: u64, settings: &RenderSettings, accumulation: &mut Accumulation, mut after_pass: F) {
+    let tiles: Vec<Tile> = tiles(settings.width, settings.height, settings.tile_size);
+    loop {
+        let active_tiles: Vec<&Tile> = tiles.iter()
+            .filter(|tile| tile.pixel_indices(settings.width).any(|idx| !accumulation.pixels[idx].is_finished(settings)))
+            .collect();
+        if active_tiles.is_empty() {
+            break;
+        }
+        let results: Vec<Vec<PixelAccumulator>> = active_tiles.par_iter()
+            .map(|tile| render_tile_pass(scene, settings, frame, tile, accumulation))
+            .collect();
+        for (tile, pixels) in active_tiles.iter().zip(results) {
+            for (pixel_idx, pixel) in tile.pixel_indices(settings.width).zip(pixels) {
+                accumulation.pixels[pixel_idx] = pixel;
             }
         }
-        v
-    };
+        after_pass(accumulation);
+    }
+}
+
+// Render and write the image, and return the number of samples of each pixel
+// (`frame` is the index of the animation frame, which decorrelates random numbers between frames)
+pub fn render<W: Write>(mut writer: io::BufWriter<W>, format: ImageFormat, scene: &Scene, frame: u64, settings: &RenderSettings) -> Vec<u32> {
+    let mut accumulation: Accumulation = Accumulation::new(settings);
+    render_passes(scene, frame, settings, &mut accumulation, |_| {});
+
+    // Write the image pixels synchronously
+    image_format::write_image(&mut writer, format, settings.width, settings.height, &accumulation.image()).unwrap();
+    accumulation.sample_counts()
+}
+
+// Write an image file atomically through a temporary file so that readers never see a partial image
+pub fn write_image_file(file_path: &path::Path, format: ImageFormat, width: u32, height: u32, colors: &[Color3]) -> io::Result<()> {
+    let mut temp_path: std::ffi::OsString = file_path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    {
+        let mut writer = io::BufWriter::new(fs::File::create(&temp_path)?);
+        image_format::write_image(&mut writer, format, width, height, colors)?;
+        writer.flush()?;
+    }
+    fs::rename(&temp_path, file_path)
+}
 
-    // Generate colors in pixels by ray tracing
-    let colors: Vec<Color3> = pos_and_seeds.par_iter().cloned().map(|((i, j), seed) | {
-        // Generate seeds
-        let seeds: Vec<u8> = {
-            let mut v: Vec<u8> = Vec::new();
-            let mut rng = util::rng_by_seed(seed);
-            for _ in 0..ns {
-                v.push(rng.gen());
+// Render into the file from the accumulation, which is rewritten with the current image every `preview_interval` while rendering,
+// calling `after_pass` after each pass, and return the final accumulation
+pub fn render_to_file<F: FnMut(&Accumulation) -> io::Result<()>>(file_path: &path::Path, format: ImageFormat, scene: &Scene, frame: u64, settings: &RenderSettings, mut accumulation: Accumulation, mut after_pass: F) -> io::Result<Accumulation> {
+    let mut last_written: Instant = Instant::now();
+    let mut pass_result: io::Result<()> = Ok(());
+    render_passes(scene, frame, settings, &mut accumulation, |accumulation| {
+        if pass_result.is_err() {
+            return;
+        }
+        if let Some(interval) = settings.preview_interval {
+            if last_written.elapsed() >= interval {
+                pass_result = write_image_file(file_path, format, settings.width, settings.height, &accumulation.image());
+                last_written = Instant::now();
             }
-            v
+        }
+        if pass_result.is_ok() {
+            pass_result = after_pass(accumulation);
+        }
+    });
+    pass_result?;
+    write_image_file(file_path, format, settings.width, settings.height, &accumulation.image())?;
+    Ok(accumulation)
+}
+
+// Write a heat-map of the numbers of samples from black (fewest) through red and yellow to white (`max_samples`)
+pub fn write_sample_heatmap<W: Write>(mut writer: io::BufWriter<W>, format: ImageFormat, settings: &RenderSettings, sample_counts: &[u32]) -> io::Result<()> {
+    let max_samples: u32 = settings.max_samples();
+    let colors: Vec<Color3> = sample_counts.iter().map(|&n| {
+        let t: f32 = if max_samples > settings.n_samples {
+            (n - settings.n_samples) as f32 / (max_samples - settings.n_samples) as f32
+        } else {
+            1.0
         };
-        let mut col = seeds.par_iter()
-            .map(|&seed| {
-                let mut rng = util::rng_by_seed(seed);
-                let u: f32 = (i as f32 + rng.gen::<f32>()) / nx as f32;
-                let v: f32 = (j as f32 + rng.gen::<f32>()) / ny as f32;
-                let r: Ray = camera.get_ray(&mut rng, u, v);
-                color(rng.borrow_mut(), &r, h, min_float, 0)
-            })
-            .reduce(|| Color3 {r: 0.0, g: 0.0, b: 0.0}, |sum, c| {
-                &sum + &c
-            });
-        col = &col / ns as f32;
-        col = Color3 {r: col.r.sqrt(), g: col.g.sqrt(), b: col.b.sqrt()};
-        col
+        // NOTE: Squared to cancel the gamma correction in the output
+        let ramp = |offset: f32| (3.0 * t - offset).clamp(0.0, 1.0).powi(2);
+        Color3 {r: ramp(0.0), g: ramp(1.0), b: ramp(2.0)}
     }).collect();
+    image_format::write_image(&mut writer, format, settings.width, settings.height, &colors)
+}
 
-    // Write the image pixels synchronously
-    for col in colors {
-        writer.write_all(format!("{} {} {}\n", col.ir(), col.ig(), col.ib()).as_bytes()).unwrap();
+// File of the animation frame (numbered from 1 in the file name)
+pub fn frame_path(anime_out_dir_path: &path::Path, format: ImageFormat, idx: usize) -> path::PathBuf {
+    anime_out_dir_path.join(format!("anime{:08}.{}", idx + 1, format.extension()))
+}
+
+// Whether the frame is in the range of frame numbers (counted from 1 as in the file names) and its file does not exist yet
+// NOTE: A frame file exists only when it is complete because it is written atomically and no preview is written
+pub fn is_frame_pending(anime_out_dir_path: &path::Path, format: ImageFormat, frames: &RangeInclusive<usize>, idx: usize) -> bool {
+    if !frames.contains(&(idx + 1)) {
+        return false;
+    }
+    let file_path = frame_path(anime_out_dir_path, format, idx);
+    if file_path.exists() {
+        println!("{:?} exists, skipped", file_path);
+        return false;
     }
+    true
 }
 
-pub fn render_animation(anime_out_dir_path: &path::Path, random_seed: u8, scene_iterator: impl Iterator<Item=Scene>, width: u32, height: u32, n_samples: u32, min_float: f32) {
+// Render the frames in the range, skipping frames whose files exist
+pub fn render_animation(anime_out_dir_path: &path::Path, format: ImageFormat, scene_iterator: impl Iterator<Item=Scene>, frames: RangeInclusive<usize>, settings: &RenderSettings) {
+    let settings: RenderSettings = RenderSettings {
+        preview_interval: None,
+        ..settings.clone()
+    };
     // Create a animation directory
     std::fs::create_dir_all(anime_out_dir_path).unwrap();
+    let frame_scenes: Vec<(usize, Scene)> = scene_iterator
+        .enumerate()
+        .take_while(|(idx, _)| *idx < *frames.end())
+        .filter(|(idx, _)| is_frame_pending(anime_out_dir_path, format, &frames, *idx))
+        .collect();
     // NOTE: collect is necessary for using .par_iter in Rayon. par_bridge can be useful but it requires Send
-    scene_iterator.enumerate().collect::<Vec<_>>().into_par_iter().for_each(|(idx, scene)| {
-        let file_path = anime_out_dir_path.join(format!("anime{:08}.ppm", *idx + 1));
-        let writer = io::BufWriter::new(fs::File::create(&file_path).unwrap());
+    frame_scenes.par_iter().for_each(|(idx, scene)| {
+        let file_path = frame_path(anime_out_dir_path, format, *idx);
         // Render by ray tracing
-        render(
-            writer,
-            random_seed,
+        render_to_file(
+            &file_path,
+            format,
             scene,
-            width,
-            height,
-            n_samples,
-            min_float
-        );
+            *idx as u64,
+            &settings,
+            Accumulation::new(&settings),
+            |_| Ok(())
+        ).unwrap();
         println!("{:?} rendered", file_path);
     });
 }
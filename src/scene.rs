@@ -1,7 +1,53 @@
+use rand::prelude::*;
 use crate::camera::Camera;
-use crate::hitable::Hitable;
+use crate::background::Background;
+use crate::hitable::{Hitable, HitRecord};
+use crate::list_hitable::ListHitable;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use core::borrow::Borrow;
 
 pub struct Scene {
     pub camera: Camera,
-    pub hitable: Box<dyn Hitable + Sync>
+    pub hitable: Box<dyn Hitable + Sync>,
+    // Emissive hitables sampled directly at each diffuse bounce (shared by Arc with ones in `hitable`)
+    pub lights: ListHitable,
+    // Light from where rays escape the scene
+    pub background: Box<dyn Background + Sync>,
+    // Medium filling the whole scene
+    pub atmosphere: Option<Atmosphere>
+}
+
+// Homogeneous medium filling the whole scene such as haze
+pub struct Atmosphere {
+    // Probability of scattering per unit distance
+    pub density: f32,
+    pub phase_function: Box<dyn Material + Sync>
+}
+
+impl Atmosphere {
+    // Scattering of the ray in the atmosphere before `t_max`
+    pub fn hit(&self, rng: &mut rand::rngs::StdRng, r: &Ray, t_max: f32) -> Option<HitRecord<'_>> {
+        // Exponentially distributed distance to scattering
+        let distance: f32 = -(1.0 - rng.gen::<f32>()).ln() / self.density;
+        let t: f32 = distance / r.direction.length();
+        if t >= t_max {
+            return None;
+        }
+        Some(HitRecord {
+            t,
+            p: r.point_at_parameter(t),
+            // NOTE: Phase functions do not depend on normals
+            normal: Vec3 {x: 1.0, y: 0.0, z: 0.0},
+            u: 0.0,
+            v: 0.0,
+            material: self.phase_function.borrow()
+        })
+    }
+
+    // Fraction of light passing through the distance
+    pub fn transmittance(&self, distance: f32) -> f32 {
+        (-self.density * distance).exp()
+    }
 }
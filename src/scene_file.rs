@@ -0,0 +1,905 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path;
+use std::sync::Arc;
+use serde::{Deserialize, Deserializer};
+use serde::de;
+
+use crate::color3::Color3;
+use crate::vec3::Vec3;
+use crate::hitable::Hitable;
+use crate::bvh_hitable::BvhHitable;
+use crate::list_hitable::ListHitable;
+use crate::sphere_hitable::SphereHitable;
+use crate::moving_sphere_hitable::MovingSphereHitable;
+use crate::triangle_hitable::TriangleHitable;
+use crate::plane_hitable::PlaneHitable;
+use crate::disk_hitable::DiskHitable;
+use crate::cylinder_hitable::CylinderHitable;
+use crate::cone_hitable::ConeHitable;
+use crate::torus_hitable::TorusHitable;
+use crate::sdf_hitable::SdfHitable;
+use crate::sdf::{Sdf, SphereSdf, BoxSdf, TorusSdf, MandelbulbSdf, TranslateSdf, UnionSdf, RepeatSdf, TwistSdf};
+use crate::rect_hitable::{XyRectHitable, XzRectHitable, YzRectHitable};
+use crate::box_hitable::BoxHitable;
+use crate::flip_normals_hitable::FlipNormalsHitable;
+use crate::transform_hitable::TransformHitable;
+use crate::csg_hitable::{CsgHitable, CsgOperation};
+use crate::mat4::Mat4;
+use crate::constant_medium_hitable::ConstantMediumHitable;
+use crate::obj;
+use crate::material::{Material, LambertMaterial, MetalMaterial, Conductor, MicrofacetMetalMaterial, GgxDistribution, Dispersion, DielectricMaterial, RoughDielectricMaterial, DiffuseLightMaterial, IsotropicMaterial, HenyeyGreensteinMaterial};
+use crate::texture::{Texture, ConstantTexture, CheckerTexture, NoiseTexture, NoiseStyle, ImageTexture};
+use crate::perlin::Perlin;
+use crate::camera::Camera;
+use crate::scene::{Scene, Atmosphere};
+use crate::background::{Background, GradientBackground, SolidBackground, EnvironmentMap};
+use crate::image_format;
+use crate::util;
+
+// Scene description file (JSON)
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneDescription {
+    camera: CameraDescription,
+    // Light from where rays escape the scene (the sky gradient by default)
+    #[serde(default)]
+    background: Option<BackgroundDescription>,
+    // Whether rays escaping the scene get light from the sky gradient (black otherwise), the older form of `background`
+    #[serde(default)]
+    sky: Option<bool>,
+    // Medium filling the whole scene
+    #[serde(default)]
+    atmosphere: Option<AtmosphereDescription>,
+    // Named objects shared by instances
+    #[serde(default)]
+    definitions: HashMap<String, ObjectDescription>,
+    objects: Vec<ObjectDescription>
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AtmosphereDescription {
+    density: f32,
+    phase_function: MaterialDescription
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum BackgroundDescription {
+    // Vertical gradient from straight down to straight up
+    Gradient {
+        #[serde(default = "default_gradient_bottom")]
+        bottom: [f32; 3],
+        #[serde(default = "default_gradient_top")]
+        top: [f32; 3]
+    },
+    Solid {
+        color: [f32; 3]
+    },
+    // Equirectangular HDR image (.hdr or .pfm) lighting the scene
+    Environment {
+        // Relative to the scene file
+        path: path::PathBuf,
+        // Angle in degrees turning the image around the Y axis
+        #[serde(default)]
+        rotation: f32,
+        #[serde(default = "default_intensity")]
+        intensity: f32
+    }
+}
+
+fn default_gradient_bottom() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+fn default_gradient_top() -> [f32; 3] {
+    [0.5, 0.7, 1.0]
+}
+
+fn default_intensity() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraDescription {
+    lookfrom: [f32; 3],
+    lookat: [f32; 3],
+    #[serde(default = "default_vup")]
+    vup: [f32; 3],
+    vfov: f32,
+    #[serde(default)]
+    aperture: f32,
+    // Distance between lookfrom and lookat by default
+    focus_dist: Option<f32>,
+    // Shutter open and close times for motion blur
+    #[serde(default)]
+    time0: f32,
+    #[serde(default)]
+    time1: f32
+}
+
+fn default_vup() -> [f32; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum ObjectDescription {
+    Sphere {
+        center: [f32; 3],
+        radius: f32,
+        material: MaterialDescription
+    },
+    // Sphere moving linearly from center0 at time0 to center1 at time1
+    MovingSphere {
+        center0: [f32; 3],
+        center1: [f32; 3],
+        time0: f32,
+        time1: f32,
+        radius: f32,
+        material: MaterialDescription
+    },
+    Triangle {
+        vertices: [[f32; 3]; 3],
+        normals: Option<[[f32; 3]; 3]>,
+        uvs: Option<[[f32; 2]; 3]>,
+        material: MaterialDescription
+    },
+    // Wavefront OBJ file
+    Mesh {
+        // Relative to the scene file
+        path: path::PathBuf,
+        // Material of faces without materials in the OBJ file
+        material: Option<MaterialDescription>
+    },
+    // Infinite plane through the point
+    Plane {
+        point: [f32; 3],
+        normal: [f32; 3],
+        material: MaterialDescription
+    },
+    Disk {
+        center: [f32; 3],
+        normal: [f32; 3],
+        radius: f32,
+        material: MaterialDescription
+    },
+    // Capped cylinder standing on the Y axis from the center of its bottom
+    Cylinder {
+        center: [f32; 3],
+        radius: f32,
+        height: f32,
+        material: MaterialDescription
+    },
+    // Capped cone standing on the Y axis from the center of its base
+    Cone {
+        center: [f32; 3],
+        radius: f32,
+        height: f32,
+        material: MaterialDescription
+    },
+    // Torus around the Y axis through the center
+    Torus {
+        center: [f32; 3],
+        major_radius: f32,
+        minor_radius: f32,
+        material: MaterialDescription
+    },
+    // Surface of a signed distance function
+    Sdf {
+        sdf: SdfDescription,
+        // Fraction of the distance stepped at once in sphere tracing (lower for twist)
+        #[serde(default = "default_step_scale")]
+        step_scale: f32,
+        material: MaterialDescription
+    },
+    // Rectangle in the XY plane at z = k, facing +Z
+    XyRect {
+        x0: f32,
+        x1: f32,
+        y0: f32,
+        y1: f32,
+        k: f32,
+        material: MaterialDescription
+    },
+    // Rectangle in the XZ plane at y = k, facing +Y
+    XzRect {
+        x0: f32,
+        x1: f32,
+        z0: f32,
+        z1: f32,
+        k: f32,
+        material: MaterialDescription
+    },
+    // Rectangle in the YZ plane at x = k, facing +X
+    YzRect {
+        y0: f32,
+        y1: f32,
+        z0: f32,
+        z1: f32,
+        k: f32,
+        material: MaterialDescription
+    },
+    // Axis-aligned box
+    Box {
+        min: [f32; 3],
+        max: [f32; 3],
+        material: MaterialDescription
+    },
+    // Object with normals flipped
+    FlipNormals {
+        object: Box<ObjectDescription>
+    },
+    // Object transformed by the transforms in order
+    Transform {
+        object: Box<ObjectDescription>,
+        transforms: Vec<TransformDescription>
+    },
+    // Object in `definitions` transformed by the transforms in order, sharing the object with other instances
+    Instance {
+        definition: String,
+        #[serde(default)]
+        transforms: Vec<TransformDescription>
+    },
+    // Constructive solid geometry of two closed objects
+    Csg {
+        operation: CsgOperationDescription,
+        left: Box<ObjectDescription>,
+        right: Box<ObjectDescription>
+    },
+    // Medium of constant density inside the convex boundary (whose material is not used)
+    ConstantMedium {
+        boundary: Box<ObjectDescription>,
+        density: f32,
+        phase_function: MaterialDescription
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum MaterialDescription {
+    Lambert {
+        #[serde(deserialize_with = "texture")]
+        albedo: TextureDescription
+    },
+    Metal {
+        #[serde(deserialize_with = "texture")]
+        albedo: TextureDescription,
+        #[serde(default)]
+        fuzz: f32
+    },
+    MicrofacetMetal {
+        conductor: ConductorDescription,
+        #[serde(default)]
+        roughness: f32,
+        #[serde(default)]
+        anisotropy: f32,
+        // Direction along which highlights are stretched by anisotropy, projected onto the surface
+        #[serde(default = "default_anisotropy_axis")]
+        anisotropy_axis: [f32; 3]
+    },
+    Dielectric {
+        ref_idx: f32,
+        // Absorption coefficient per unit distance inside
+        #[serde(default)]
+        absorption: Option<[f32; 3]>,
+        // Index of refraction by the wavelength in spectral rendering
+        #[serde(default)]
+        dispersion: Option<DispersionDescription>
+    },
+    RoughDielectric {
+        ref_idx: f32,
+        #[serde(default)]
+        absorption: Option<[f32; 3]>,
+        #[serde(default)]
+        dispersion: Option<DispersionDescription>,
+        roughness: f32
+    },
+    DiffuseLight {
+        emit: [f32; 3]
+    },
+    // Phase functions of media
+    Isotropic {
+        #[serde(deserialize_with = "texture")]
+        albedo: TextureDescription
+    },
+    HenyeyGreenstein {
+        #[serde(deserialize_with = "texture")]
+        albedo: TextureDescription,
+        g: f32
+    }
+}
+
+fn default_anisotropy_axis() -> [f32; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum DispersionDescription {
+    Cauchy {
+        a: f32,
+        b: f32
+    },
+    Sellmeier {
+        b: [f32; 3],
+        c: [f32; 3]
+    }
+}
+
+impl DispersionDescription {
+    fn to_dispersion(&self) -> Dispersion {
+        match self {
+            DispersionDescription::Cauchy {a, b} => Dispersion::Cauchy {a: *a, b: *b},
+            DispersionDescription::Sellmeier {b, c} => Dispersion::Sellmeier {b: *b, c: *c}
+        }
+    }
+}
+
+fn default_step_scale() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum SdfDescription {
+    Sphere {
+        radius: f32
+    },
+    // Box with edges rounded by the radius
+    Box {
+        half_extents: [f32; 3],
+        #[serde(default)]
+        radius: f32
+    },
+    Torus {
+        major_radius: f32,
+        minor_radius: f32
+    },
+    Mandelbulb {
+        #[serde(default = "default_mandelbulb_power")]
+        power: f32,
+        #[serde(default = "default_mandelbulb_iterations")]
+        iterations: u32
+    },
+    Translate {
+        offset: [f32; 3],
+        sdf: Box<SdfDescription>
+    },
+    // Union blending surfaces within the smoothness
+    Union {
+        sdfs: Vec<SdfDescription>,
+        #[serde(default)]
+        smoothness: f32
+    },
+    // Infinite repetition with the period on each axis (0 for no repetition)
+    Repeat {
+        period: [f32; 3],
+        sdf: Box<SdfDescription>
+    },
+    // Twist around the Y axis by the angle in degrees per unit height
+    Twist {
+        angle: f32,
+        sdf: Box<SdfDescription>
+    }
+}
+
+fn default_mandelbulb_power() -> f32 {
+    8.0
+}
+
+fn default_mandelbulb_iterations() -> u32 {
+    8
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CsgOperationDescription {
+    Union,
+    Intersection,
+    // Left minus right
+    Difference
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum TransformDescription {
+    Translate([f32; 3]),
+    Scale([f32; 3]),
+    // Counterclockwise rotation around the axis by the angle in degrees
+    Rotate {
+        axis: [f32; 3],
+        angle: f32
+    },
+    // Upper three rows of the 4x4 matrix of an affine transform
+    Matrix([[f32; 4]; 3])
+}
+
+// Conductor written as a preset name or its complex index of refraction {"eta": [r, g, b], "k": [r, g, b]}
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ConductorDescription {
+    Preset(ConductorPresetDescription),
+    Ior {
+        eta: [f32; 3],
+        k: [f32; 3]
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ConductorPresetDescription {
+    Gold,
+    Copper,
+    Aluminium
+}
+
+// Texture written as an object such as {"checker": {...}} or a constant color [r, g, b]
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum TextureDescription {
+    Constant([f32; 3]),
+    Checker {
+        #[serde(deserialize_with = "boxed_texture")]
+        odd: Box<TextureDescription>,
+        #[serde(deserialize_with = "boxed_texture")]
+        even: Box<TextureDescription>,
+        #[serde(default = "default_checker_scale")]
+        scale: f32
+    },
+    Noise {
+        #[serde(default = "default_noise_scale")]
+        scale: f32,
+        #[serde(default)]
+        style: NoiseStyleDescription,
+        #[serde(default = "default_noise_color")]
+        color: [f32; 3],
+        // Random seed of the Perlin noise
+        #[serde(default)]
+        seed: u64
+    },
+    Image {
+        // Relative to the scene file
+        path: path::PathBuf
+    }
+}
+
+fn default_checker_scale() -> f32 {
+    10.0
+}
+
+fn default_noise_scale() -> f32 {
+    1.0
+}
+
+fn default_noise_color() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+enum NoiseStyleDescription {
+    #[default]
+    Perlin,
+    Turbulence,
+    Marble
+}
+
+fn texture<'de, D: Deserializer<'de>>(deserializer: D) -> Result<TextureDescription, D::Error> {
+    struct TextureVisitor;
+
+    impl<'de> de::Visitor<'de> for TextureVisitor {
+        type Value = TextureDescription;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a color [r, g, b] or a texture object")
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            Deserialize::deserialize(de::value::SeqAccessDeserializer::new(seq)).map(TextureDescription::Constant)
+        }
+
+        fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            Deserialize::deserialize(de::value::MapAccessDeserializer::new(map))
+        }
+    }
+
+    deserializer.deserialize_any(TextureVisitor)
+}
+
+fn boxed_texture<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Box<TextureDescription>, D::Error> {
+    texture(deserializer).map(Box::new)
+}
+
+#[derive(Debug)]
+pub enum SceneFileError {
+    Io(std::io::Error),
+    // Failed to load a file referred from the scene file
+    Load {
+        file_path: path::PathBuf,
+        err: std::io::Error
+    },
+    // `field` is the path to the wrong field such as "objects[2].material.albedo"
+    Parse {
+        field: String,
+        line: usize,
+        column: usize,
+        message: String
+    },
+    // Well-formed but inconsistent description such as an instance of an unknown definition
+    Invalid(String)
+}
+
+impl fmt::Display for SceneFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneFileError::Io(err) => write!(f, "{}", err),
+            SceneFileError::Load {file_path, err} => write!(f, "{}: {}", file_path.display(), err),
+            SceneFileError::Parse {field, line, column, message} => {
+                write!(f, "line {} column {}: {}: {}", line, column, field, message)
+            },
+            SceneFileError::Invalid(message) => write!(f, "{}", message)
+        }
+    }
+}
+
+fn vec3(a: [f32; 3]) -> Vec3 {
+    Vec3 {x: a[0], y: a[1], z: a[2]}
+}
+
+fn color3(a: [f32; 3]) -> Color3 {
+    Color3 {r: a[0], g: a[1], b: a[2]}
+}
+
+impl CameraDescription {
+    fn to_camera(&self, width: u32, height: u32) -> Camera {
+        let lookfrom: Vec3 = vec3(self.lookfrom);
+        let lookat: Vec3 = vec3(self.lookat);
+        Camera {
+            lookfrom,
+            lookat,
+            vup: vec3(self.vup),
+            vfov: self.vfov,
+            aspect: width as f32 / height as f32,
+            aperture: self.aperture,
+            focus_dist: self.focus_dist.unwrap_or_else(|| (&lookfrom - &lookat).length()),
+            time0: self.time0,
+            time1: self.time1
+        }
+    }
+}
+
+// Hitable of a definition shared by instances, and whether it is emissive
+struct Definition {
+    hitable: Arc<dyn Hitable + Sync>,
+    light: bool
+}
+
+struct Definitions(HashMap<String, Definition>);
+
+impl Definitions {
+    fn get(&self, name: &str) -> Result<&Definition, SceneFileError> {
+        self.0.get(name).ok_or_else(|| SceneFileError::Invalid(format!("undefined definition: {}", name)))
+    }
+}
+
+impl SdfDescription {
+    fn to_sdf(&self) -> Box<dyn Sdf + Sync> {
+        match self {
+            SdfDescription::Sphere {radius} => Box::new(SphereSdf {radius: *radius}),
+            SdfDescription::Box {half_extents, radius} => Box::new(BoxSdf {half_extents: vec3(*half_extents), radius: *radius}),
+            SdfDescription::Torus {major_radius, minor_radius} => Box::new(TorusSdf {
+                major_radius: *major_radius,
+                minor_radius: *minor_radius
+            }),
+            SdfDescription::Mandelbulb {power, iterations} => Box::new(MandelbulbSdf {power: *power, iterations: *iterations}),
+            SdfDescription::Translate {offset, sdf} => Box::new(TranslateSdf {offset: vec3(*offset), sdf: sdf.to_sdf()}),
+            SdfDescription::Union {sdfs, smoothness} => Box::new(UnionSdf {
+                sdfs: sdfs.iter().map(|sdf| sdf.to_sdf()).collect(),
+                smoothness: *smoothness
+            }),
+            SdfDescription::Repeat {period, sdf} => Box::new(RepeatSdf {period: vec3(*period), sdf: sdf.to_sdf()}),
+            SdfDescription::Twist {angle, sdf} => Box::new(TwistSdf {rate: angle.to_radians(), sdf: sdf.to_sdf()})
+        }
+    }
+}
+
+impl TransformDescription {
+    fn to_matrix(&self) -> Mat4 {
+        match self {
+            TransformDescription::Translate(offset) => Mat4::translate(&vec3(*offset)),
+            TransformDescription::Scale(factors) => Mat4::scale(&vec3(*factors)),
+            TransformDescription::Rotate {axis, angle} => Mat4::rotate(&vec3(*axis), *angle),
+            TransformDescription::Matrix(rows) => Mat4 {m: [rows[0], rows[1], rows[2], [0.0, 0.0, 0.0, 1.0]]}
+        }
+    }
+}
+
+fn transform_hitable(hitable: Arc<dyn Hitable + Sync>, transforms: &[TransformDescription]) -> Result<TransformHitable, SceneFileError> {
+    let matrix: Mat4 = transforms.iter().fold(Mat4::identity(), |matrix, transform| &transform.to_matrix() * &matrix);
+    TransformHitable::new(hitable, matrix).ok_or_else(|| SceneFileError::Invalid("singular transform".to_string()))
+}
+
+// NOTE: `base_dir` is the directory of the scene file to resolve relative paths
+impl ObjectDescription {
+    fn to_hitable(&self, base_dir: &path::Path, definitions: &Definitions) -> Result<Box<dyn Hitable + Sync>, SceneFileError> {
+        Ok(match self {
+            ObjectDescription::Sphere {center, radius, material} => Box::new(SphereHitable {
+                center: vec3(*center),
+                radius: *radius,
+                material: material.to_material(base_dir)?
+            }),
+            ObjectDescription::MovingSphere {center0, center1, time0, time1, radius, material} => Box::new(MovingSphereHitable {
+                center0: vec3(*center0),
+                center1: vec3(*center1),
+                time0: *time0,
+                time1: *time1,
+                radius: *radius,
+                material: material.to_material(base_dir)?
+            }),
+            ObjectDescription::Triangle {vertices, normals, uvs, material} => Box::new(TriangleHitable {
+                vertices: [vec3(vertices[0]), vec3(vertices[1]), vec3(vertices[2])],
+                normals: normals.map(|n| [vec3(n[0]), vec3(n[1]), vec3(n[2])]),
+                uvs: uvs.map(|t| [(t[0][0], t[0][1]), (t[1][0], t[1][1]), (t[2][0], t[2][1])]),
+                material: material.to_material(base_dir)?
+            }),
+            ObjectDescription::Mesh {path, material} => {
+                let default_material: Box<dyn Material + Sync> = match material {
+                    Some(material) => material.to_material(base_dir)?,
+                    None => Box::new(LambertMaterial {albedo: Box::new(ConstantTexture {color: Color3 {r: 0.8, g: 0.8, b: 0.8}})})
+                };
+                let file_path: path::PathBuf = base_dir.join(path);
+                Box::new(obj::load_obj(&file_path, default_material).map_err(|err| SceneFileError::Load {file_path, err})?)
+            },
+            ObjectDescription::Plane {point, normal, material} => Box::new(PlaneHitable {
+                point: vec3(*point),
+                normal: vec3(*normal),
+                material: material.to_material(base_dir)?
+            }),
+            ObjectDescription::Disk {center, normal, radius, material} => Box::new(DiskHitable {
+                center: vec3(*center),
+                normal: vec3(*normal),
+                radius: *radius,
+                material: material.to_material(base_dir)?
+            }),
+            ObjectDescription::Cylinder {center, radius, height, material} => Box::new(CylinderHitable {
+                center: vec3(*center),
+                radius: *radius,
+                height: *height,
+                material: material.to_material(base_dir)?
+            }),
+            ObjectDescription::Cone {center, radius, height, material} => Box::new(ConeHitable {
+                center: vec3(*center),
+                radius: *radius,
+                height: *height,
+                material: material.to_material(base_dir)?
+            }),
+            ObjectDescription::Torus {center, major_radius, minor_radius, material} => Box::new(TorusHitable {
+                center: vec3(*center),
+                major_radius: *major_radius,
+                minor_radius: *minor_radius,
+                material: material.to_material(base_dir)?
+            }),
+            ObjectDescription::Sdf {sdf, step_scale, material} => Box::new(SdfHitable {
+                sdf: sdf.to_sdf(),
+                step_scale: *step_scale,
+                material: material.to_material(base_dir)?
+            }),
+            ObjectDescription::XyRect {x0, x1, y0, y1, k, material} => Box::new(XyRectHitable {
+                x0: *x0,
+                x1: *x1,
+                y0: *y0,
+                y1: *y1,
+                k: *k,
+                material: material.to_material(base_dir)?
+            }),
+            ObjectDescription::XzRect {x0, x1, z0, z1, k, material} => Box::new(XzRectHitable {
+                x0: *x0,
+                x1: *x1,
+                z0: *z0,
+                z1: *z1,
+                k: *k,
+                material: material.to_material(base_dir)?
+            }),
+            ObjectDescription::YzRect {y0, y1, z0, z1, k, material} => Box::new(YzRectHitable {
+                y0: *y0,
+                y1: *y1,
+                z0: *z0,
+                z1: *z1,
+                k: *k,
+                material: material.to_material(base_dir)?
+            }),
+            ObjectDescription::Box {min, max, material} => Box::new(BoxHitable::new(vec3(*min), vec3(*max), material.to_material(base_dir)?)),
+            ObjectDescription::FlipNormals {object} => Box::new(FlipNormalsHitable {hitable: object.to_hitable(base_dir, definitions)?}),
+            ObjectDescription::Transform {object, transforms} => {
+                Box::new(transform_hitable(Arc::from(object.to_hitable(base_dir, definitions)?), transforms)?)
+            },
+            ObjectDescription::Instance {definition, transforms} => {
+                Box::new(transform_hitable(definitions.get(definition)?.hitable.clone(), transforms)?)
+            },
+            ObjectDescription::Csg {operation, left, right} => Box::new(CsgHitable {
+                left: left.to_hitable(base_dir, definitions)?,
+                right: right.to_hitable(base_dir, definitions)?,
+                operation: match operation {
+                    CsgOperationDescription::Union => CsgOperation::Union,
+                    CsgOperationDescription::Intersection => CsgOperation::Intersection,
+                    CsgOperationDescription::Difference => CsgOperation::Difference
+                }
+            }),
+            ObjectDescription::ConstantMedium {boundary, density, phase_function} => Box::new(ConstantMediumHitable {
+                boundary: boundary.to_hitable(base_dir, definitions)?,
+                density: *density,
+                phase_function: phase_function.to_material(base_dir)?
+            })
+        })
+    }
+}
+
+impl ObjectDescription {
+    // Whether the object is emissive and sampled directly as a light
+    fn is_light(&self, definitions: &Definitions) -> bool {
+        match self {
+            ObjectDescription::Sphere {material: MaterialDescription::DiffuseLight {..}, ..} |
+            ObjectDescription::Triangle {material: MaterialDescription::DiffuseLight {..}, ..} |
+            ObjectDescription::Disk {material: MaterialDescription::DiffuseLight {..}, ..} |
+            ObjectDescription::XyRect {material: MaterialDescription::DiffuseLight {..}, ..} |
+            ObjectDescription::XzRect {material: MaterialDescription::DiffuseLight {..}, ..} |
+            ObjectDescription::YzRect {material: MaterialDescription::DiffuseLight {..}, ..} => true,
+            ObjectDescription::FlipNormals {object} | ObjectDescription::Transform {object, ..} => object.is_light(definitions),
+            ObjectDescription::Instance {definition, ..} => definitions.0.get(definition).is_some_and(|definition| definition.light),
+            _ => false
+        }
+    }
+}
+
+impl MaterialDescription {
+    fn to_material(&self, base_dir: &path::Path) -> Result<Box<dyn Material + Sync>, SceneFileError> {
+        Ok(match self {
+            MaterialDescription::Lambert {albedo} => Box::new(LambertMaterial {albedo: albedo.to_texture(base_dir)?}),
+            MaterialDescription::Metal {albedo, fuzz} => Box::new(MetalMaterial {albedo: albedo.to_texture(base_dir)?, f: *fuzz}),
+            MaterialDescription::MicrofacetMetal {conductor, roughness, anisotropy, anisotropy_axis} => {
+                let (eta, k) = match conductor {
+                    ConductorDescription::Preset(preset) => match preset {
+                        ConductorPresetDescription::Gold => Conductor::Gold,
+                        ConductorPresetDescription::Copper => Conductor::Copper,
+                        ConductorPresetDescription::Aluminium => Conductor::Aluminium
+                    }.ior(),
+                    ConductorDescription::Ior {eta, k} => (color3(*eta), color3(*k))
+                };
+                Box::new(MicrofacetMetalMaterial {
+                    eta,
+                    k,
+                    distribution: GgxDistribution::new(*roughness, *anisotropy),
+                    anisotropy_axis: vec3(*anisotropy_axis)
+                })
+            },
+            MaterialDescription::Dielectric {ref_idx, absorption, dispersion} => Box::new(DielectricMaterial {
+                ref_idx: *ref_idx,
+                absorption: absorption.map(color3),
+                dispersion: dispersion.as_ref().map(|dispersion| dispersion.to_dispersion())
+            }),
+            MaterialDescription::RoughDielectric {ref_idx, absorption, dispersion, roughness} => Box::new(RoughDielectricMaterial {
+                ref_idx: *ref_idx,
+                absorption: absorption.map(color3),
+                dispersion: dispersion.as_ref().map(|dispersion| dispersion.to_dispersion()),
+                distribution: GgxDistribution::new(*roughness, 0.0)
+            }),
+            MaterialDescription::DiffuseLight {emit} => Box::new(DiffuseLightMaterial {emit: color3(*emit)}),
+            MaterialDescription::Isotropic {albedo} => Box::new(IsotropicMaterial {albedo: albedo.to_texture(base_dir)?}),
+            MaterialDescription::HenyeyGreenstein {albedo, g} => Box::new(HenyeyGreensteinMaterial {albedo: albedo.to_texture(base_dir)?, g: *g})
+        })
+    }
+}
+
+impl TextureDescription {
+    fn to_texture(&self, base_dir: &path::Path) -> Result<Box<dyn Texture + Sync>, SceneFileError> {
+        Ok(match self {
+            TextureDescription::Constant(color) => Box::new(ConstantTexture {color: color3(*color)}),
+            TextureDescription::Checker {odd, even, scale} => Box::new(CheckerTexture {
+                odd: odd.to_texture(base_dir)?,
+                even: even.to_texture(base_dir)?,
+                scale: *scale
+            }),
+            TextureDescription::Noise {scale, style, color, seed} => Box::new(NoiseTexture {
+                perlin: Perlin::new(&mut util::rng_by_seed(*seed)),
+                scale: *scale,
+                style: match style {
+                    NoiseStyleDescription::Perlin => NoiseStyle::Perlin,
+                    NoiseStyleDescription::Turbulence => NoiseStyle::Turbulence,
+                    NoiseStyleDescription::Marble => NoiseStyle::Marble
+                },
+                color: color3(*color)
+            }),
+            TextureDescription::Image {path} => {
+                let file_path: path::PathBuf = base_dir.join(path);
+                let (width, height, pixels) = image_format::read_image(&file_path)
+                    .map_err(|err| SceneFileError::Load {file_path, err})?;
+                Box::new(ImageTexture {width, height, pixels})
+            }
+        })
+    }
+}
+
+impl BackgroundDescription {
+    fn to_background(&self, base_dir: &path::Path) -> Result<Box<dyn Background + Sync>, SceneFileError> {
+        Ok(match self {
+            BackgroundDescription::Gradient {bottom, top} => Box::new(GradientBackground {bottom: color3(*bottom), top: color3(*top)}),
+            BackgroundDescription::Solid {color} => Box::new(SolidBackground {color: color3(*color)}),
+            BackgroundDescription::Environment {path, rotation, intensity} => {
+                let file_path: path::PathBuf = base_dir.join(path);
+                let (width, height, pixels) = image_format::read_image(&file_path)
+                    .map_err(|err| SceneFileError::Load {file_path, err})?;
+                Box::new(EnvironmentMap::new(width, height, pixels, *rotation, *intensity))
+            }
+        })
+    }
+}
+
+fn parse(json: &str) -> Result<SceneDescription, SceneFileError> {
+    let deserializer = &mut serde_json::Deserializer::from_str(json);
+    serde_path_to_error::deserialize(deserializer).map_err(|err| {
+        let inner: &serde_json::Error = err.inner();
+        // NOTE: serde_json appends the position to the message, which is reported separately
+        let message: String = inner.to_string();
+        let message: String = match message.rfind(" at line ") {
+            Some(idx) => message[..idx].to_string(),
+            None => message
+        };
+        SceneFileError::Parse {
+            field: err.path().to_string(),
+            line: inner.line(),
+            column: inner.column(),
+            message
+        }
+    })
+}
+
+// Load a scene from a scene description file
+pub fn load(file_path: &path::Path, width: u32, height: u32) -> Result<Scene, SceneFileError> {
+    let json: String = fs::read_to_string(file_path).map_err(SceneFileError::Io)?;
+    let description: SceneDescription = parse(&json)?;
+    let base_dir: &path::Path = file_path.parent().unwrap_or_else(|| path::Path::new("."));
+    // NOTE: Definitions cannot refer to other definitions
+    let no_definitions: Definitions = Definitions(HashMap::new());
+    let mut definitions: Definitions = Definitions(HashMap::new());
+    for (name, o) in &description.definitions {
+        definitions.0.insert(name.clone(), Definition {
+            hitable: Arc::from(o.to_hitable(base_dir, &no_definitions)?),
+            light: o.is_light(&no_definitions)
+        });
+    }
+    let mut hitables: Vec<Box<dyn Hitable + Sync>> = Vec::new();
+    let mut lights: Vec<Box<dyn Hitable + Sync>> = Vec::new();
+    for o in &description.objects {
+        let hitable: Box<dyn Hitable + Sync> = o.to_hitable(base_dir, &definitions)?;
+        // NOTE: Emissive objects are shared with the lights
+        if o.is_light(&definitions) {
+            let hitable: Arc<dyn Hitable + Sync> = Arc::from(hitable);
+            lights.push(Box::new(hitable.clone()));
+            hitables.push(Box::new(hitable));
+        } else {
+            hitables.push(hitable);
+        }
+    }
+    Ok(Scene {
+        camera: description.camera.to_camera(width, height),
+        hitable: Box::new(BvhHitable::new(hitables)),
+        lights: ListHitable {hitables: lights},
+        background: match (&description.background, description.sky) {
+            (Some(_), Some(_)) => return Err(SceneFileError::Invalid("sky and background cannot be given together".to_string())),
+            (Some(background), None) => background.to_background(base_dir)?,
+            (None, Some(false)) => Box::new(SolidBackground {color: Color3 {r: 0.0, g: 0.0, b: 0.0}}),
+            (None, _) => Box::new(GradientBackground::sky())
+        },
+        atmosphere: match &description.atmosphere {
+            Some(atmosphere) => Some(Atmosphere {
+                density: atmosphere.density,
+                phase_function: atmosphere.phase_function.to_material(base_dir)?
+            }),
+            None => None
+        }
+    })
+}
@@ -0,0 +1,197 @@
+use crate::vec3::Vec3;
+use crate::aabb::Aabb;
+
+// Signed distance function, negative inside
+// NOTE: Operators such as twist only give a bound of the distance, which SdfHitable compensates by its step scale
+pub trait Sdf: Send {
+    fn distance(&self, p: &Vec3) -> f32;
+    // Bounding box of the surface (None if it is unbounded)
+    fn bounding_box(&self) -> Option<Aabb>;
+}
+
+fn symmetric_box(extent: &Vec3) -> Aabb {
+    Aabb {min: -extent, max: *extent}
+}
+
+pub struct SphereSdf {
+    pub radius: f32
+}
+
+impl Sdf for SphereSdf {
+    fn distance(&self, p: &Vec3) -> f32 {
+        p.length() - self.radius
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(symmetric_box(&Vec3 {x: self.radius, y: self.radius, z: self.radius}))
+    }
+}
+
+// Box centered at the origin, whose edges are rounded by the radius outside the half extents
+pub struct BoxSdf {
+    pub half_extents: Vec3,
+    pub radius: f32
+}
+
+impl Sdf for BoxSdf {
+    fn distance(&self, p: &Vec3) -> f32 {
+        let q: Vec3 = Vec3 {
+            x: p.x.abs() - self.half_extents.x,
+            y: p.y.abs() - self.half_extents.y,
+            z: p.z.abs() - self.half_extents.z
+        };
+        let outside: Vec3 = Vec3 {x: q.x.max(0.0), y: q.y.max(0.0), z: q.z.max(0.0)};
+        outside.length() + q.x.max(q.y).max(q.z).min(0.0) - self.radius
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r: Vec3 = Vec3 {x: self.radius, y: self.radius, z: self.radius};
+        Some(symmetric_box(&(&self.half_extents + &r)))
+    }
+}
+
+// Torus around the Y axis
+pub struct TorusSdf {
+    pub major_radius: f32,
+    pub minor_radius: f32
+}
+
+impl Sdf for TorusSdf {
+    fn distance(&self, p: &Vec3) -> f32 {
+        let axial_distance: f32 = (p.x * p.x + p.z * p.z).sqrt() - self.major_radius;
+        (axial_distance * axial_distance + p.y * p.y).sqrt() - self.minor_radius
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent: f32 = self.major_radius + self.minor_radius;
+        Some(symmetric_box(&Vec3 {x: extent, y: self.minor_radius, z: extent}))
+    }
+}
+
+// Mandelbulb fractal of the power with the Y axis up, by its distance estimator
+pub struct MandelbulbSdf {
+    pub power: f32,
+    pub iterations: u32
+}
+
+impl Sdf for MandelbulbSdf {
+    fn distance(&self, p: &Vec3) -> f32 {
+        let mut z: Vec3 = *p;
+        // Derivative of |z|
+        let mut dr: f32 = 1.0;
+        let mut r: f32 = z.length();
+        for _ in 0..self.iterations {
+            if r > 2.0 || r == 0.0 {
+                break;
+            }
+            let theta: f32 = (z.y / r).acos() * self.power;
+            let phi: f32 = z.z.atan2(z.x) * self.power;
+            dr = r.powf(self.power - 1.0) * self.power * dr + 1.0;
+            let zr: f32 = r.powf(self.power);
+            z = &Vec3 {x: zr * theta.sin() * phi.cos(), y: zr * theta.cos(), z: zr * theta.sin() * phi.sin()} + p;
+            r = z.length();
+        }
+        if r == 0.0 {
+            return 0.0;
+        }
+        0.5 * r.ln() * r / dr
+    }
+
+    // NOTE: The bulb is in the sphere of radius 2 where orbits do not escape
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(symmetric_box(&Vec3 {x: 2.0, y: 2.0, z: 2.0}))
+    }
+}
+
+pub struct TranslateSdf {
+    pub offset: Vec3,
+    pub sdf: Box<dyn Sdf + Sync>
+}
+
+impl Sdf for TranslateSdf {
+    fn distance(&self, p: &Vec3) -> f32 {
+        self.sdf.distance(&(p - &self.offset))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let bbox: Aabb = self.sdf.bounding_box()?;
+        Some(Aabb {min: &bbox.min + &self.offset, max: &bbox.max + &self.offset})
+    }
+}
+
+// Union blending surfaces within the smoothness (polynomial smooth minimum, hard union at 0)
+pub struct UnionSdf {
+    pub sdfs: Vec<Box<dyn Sdf + Sync>>,
+    pub smoothness: f32
+}
+
+impl Sdf for UnionSdf {
+    fn distance(&self, p: &Vec3) -> f32 {
+        let k: f32 = self.smoothness;
+        self.sdfs.iter().map(|sdf| sdf.distance(p)).fold(f32::MAX, |a, b| {
+            if k <= 0.0 || a == f32::MAX {
+                return a.min(b);
+            }
+            let h: f32 = (0.5 + 0.5 * (b - a) / k).clamp(0.0, 1.0);
+            b + (a - b) * h - k * h * (1.0 - h)
+        })
+    }
+
+    // NOTE: Blending bulges surfaces out by at most a quarter of the smoothness
+    fn bounding_box(&self) -> Option<Aabb> {
+        let mut bbox_opt: Option<Aabb> = None;
+        for sdf in &self.sdfs {
+            let bbox: Aabb = sdf.bounding_box()?;
+            bbox_opt = Some(match bbox_opt {
+                Some(b) => b.surrounding_box(&bbox),
+                None => bbox
+            });
+        }
+        let bbox: Aabb = bbox_opt?;
+        let margin: Vec3 = Vec3 {x: self.smoothness / 4.0, y: self.smoothness / 4.0, z: self.smoothness / 4.0};
+        Some(Aabb {min: &bbox.min - &margin, max: &bbox.max + &margin})
+    }
+}
+
+// Infinite repetition with the period on each axis (no repetition on axes with period 0)
+// NOTE: The repeated surface should fit in a cell around the origin
+pub struct RepeatSdf {
+    pub period: Vec3,
+    pub sdf: Box<dyn Sdf + Sync>
+}
+
+impl Sdf for RepeatSdf {
+    fn distance(&self, p: &Vec3) -> f32 {
+        let repeat = |x: f32, period: f32| if period > 0.0 { x - period * (x / period).round() } else { x };
+        self.sdf.distance(&Vec3 {x: repeat(p.x, self.period.x), y: repeat(p.y, self.period.y), z: repeat(p.z, self.period.z)})
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+// Twist around the Y axis by the angle in radians per unit height
+pub struct TwistSdf {
+    pub rate: f32,
+    pub sdf: Box<dyn Sdf + Sync>
+}
+
+impl Sdf for TwistSdf {
+    fn distance(&self, p: &Vec3) -> f32 {
+        let (sin, cos) = (-self.rate * p.y).sin_cos();
+        self.sdf.distance(&Vec3 {x: cos * p.x - sin * p.z, y: p.y, z: sin * p.x + cos * p.z})
+    }
+
+    // Cylinder around the Y axis containing the box, which any twist stays in
+    fn bounding_box(&self) -> Option<Aabb> {
+        let bbox: Aabb = self.sdf.bounding_box()?;
+        let x: f32 = bbox.min.x.abs().max(bbox.max.x.abs());
+        let z: f32 = bbox.min.z.abs().max(bbox.max.z.abs());
+        let radius: f32 = (x * x + z * z).sqrt();
+        Some(Aabb {
+            min: Vec3 {x: -radius, y: bbox.min.y, z: -radius},
+            max: Vec3 {x: radius, y: bbox.max.y, z: radius}
+        })
+    }
+}
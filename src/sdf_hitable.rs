@@ -0,0 +1,77 @@
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::hitable::Hitable;
+use crate::hitable::HitRecord;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::sdf::Sdf;
+use core::borrow::Borrow;
+
+const MAX_STEPS: u32 = 512;
+// Distance where the surface is hit
+const EPSILON: f32 = 1e-4;
+// Distance along rays marched for unbounded surfaces
+const MAX_DISTANCE: f32 = 1000.0;
+
+// Surface of a signed distance function intersected by sphere tracing
+pub struct SdfHitable {
+    pub sdf: Box<dyn Sdf + Sync>,
+    // Fraction of the distance stepped at once, below 1 for functions which are not exact distances such as twist
+    pub step_scale: f32,
+    pub material: Box<dyn Material + std::marker::Sync>
+}
+
+impl SdfHitable {
+    // Gradient of the distance by central differences
+    fn normal(&self, p: &Vec3) -> Vec3 {
+        let gradient = |axis: Vec3| self.sdf.distance(&(p + &axis)) - self.sdf.distance(&(p - &axis));
+        Vec3 {
+            x: gradient(Vec3 {x: EPSILON, y: 0.0, z: 0.0}),
+            y: gradient(Vec3 {x: 0.0, y: EPSILON, z: 0.0}),
+            z: gradient(Vec3 {x: 0.0, y: 0.0, z: EPSILON})
+        }.unit_vector()
+    }
+}
+
+impl Hitable for SdfHitable {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let length: f32 = r.direction.length();
+        let (t_start, t_end) = match self.sdf.bounding_box() {
+            Some(bbox) => bbox.hit_interval(r, t_min, t_max)?,
+            None => (t_min, t_max.min(MAX_DISTANCE / length))
+        };
+        let mut t: f32 = t_start;
+        let start_distance: f32 = self.sdf.distance(&r.point_at_parameter(t));
+        // Rays starting inside march to the exit by the negated distance
+        let side: f32 = if start_distance < 0.0 { -1.0 } else { 1.0 };
+        // A ray starting on the surface and going away from it, such as one scattered at a grazing angle,
+        // marches off the surface before hits are accepted so that it does not hit the surface it left
+        let mut leaving: bool = side * start_distance < EPSILON
+            && side * self.sdf.distance(&r.point_at_parameter(t + EPSILON / length)) > side * start_distance;
+        for _ in 0..MAX_STEPS {
+            let p: Vec3 = r.point_at_parameter(t);
+            let distance: f32 = side * self.sdf.distance(&p);
+            if distance >= EPSILON {
+                leaving = false;
+            } else if !leaving {
+                return Some(HitRecord {
+                    t,
+                    normal: self.normal(&p),
+                    p,
+                    u: 0.0,
+                    v: 0.0,
+                    material: self.material.borrow()
+                });
+            }
+            t += self.step_scale * distance.max(EPSILON) / length;
+            if t > t_end {
+                return None;
+            }
+        }
+        None
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.sdf.bounding_box()
+    }
+}
@@ -0,0 +1,66 @@
+use crate::color3::Color3;
+
+// Spectral rendering: each path carries a wavelength sampled uniformly in the visible range.
+// Colors are turned into spectra by smooth red, green and blue bands summing to one, so that white is a flat spectrum,
+// and the radiance at the wavelength is converted to linear sRGB through CIE XYZ.
+
+pub const MIN_WAVELENGTH: f32 = 380.0;
+pub const MAX_WAVELENGTH: f32 = 720.0;
+
+// Linear sRGB of the flat spectrum of radiance 1 integrated over the visible range (used to make it white)
+const FLAT_SPECTRUM_RGB: [f32; 3] = [128.3607, 101.5381, 97.0509];
+
+// Wavelength from a uniform random number in [0, 1)
+pub fn sample_wavelength(u: f32) -> f32 {
+    MIN_WAVELENGTH + u * (MAX_WAVELENGTH - MIN_WAVELENGTH)
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t: f32 = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// Value of the spectrum of the color at the wavelength as a gray color (the color itself without a wavelength)
+pub fn at_wavelength(c: &Color3, wavelength: Option<f32>) -> Color3 {
+    match wavelength {
+        Some(wavelength) => {
+            let blue: f32 = 1.0 - smoothstep(460.0, 510.0, wavelength);
+            let red: f32 = smoothstep(570.0, 620.0, wavelength);
+            let green: f32 = 1.0 - blue - red;
+            let value: f32 = c.r * red + c.g * green + c.b * blue;
+            Color3 {r: value, g: value, b: value}
+        },
+        None => *c
+    }
+}
+
+// Lobe of the piecewise Gaussian with different widths on either side of the mean
+fn gaussian(x: f32, mean: f32, sigma_left: f32, sigma_right: f32) -> f32 {
+    let t: f32 = (x - mean) / if x < mean { sigma_left } else { sigma_right };
+    (-0.5 * t * t).exp()
+}
+
+// CIE 1931 color matching functions by the multi-lobe fit of Wyman, Sloan and Shirley (2013)
+fn color_matching(wavelength: f32) -> (f32, f32, f32) {
+    let x: f32 = 1.056 * gaussian(wavelength, 599.8, 37.9, 31.0)
+        + 0.362 * gaussian(wavelength, 442.0, 16.0, 26.7)
+        - 0.065 * gaussian(wavelength, 501.1, 20.4, 26.2);
+    let y: f32 = 0.821 * gaussian(wavelength, 568.8, 46.9, 40.5)
+        + 0.286 * gaussian(wavelength, 530.9, 16.3, 31.1);
+    let z: f32 = 1.217 * gaussian(wavelength, 437.0, 11.8, 36.0)
+        + 0.681 * gaussian(wavelength, 459.0, 26.0, 13.8);
+    (x, y, z)
+}
+
+// Linear sRGB (D65) of the radiance at the uniformly sampled wavelength divided by its density,
+// whose mean over wavelengths is the color of the spectrum
+// NOTE: The conversion from XYZ is linear, so summing samples in sRGB is the same as converting the sum of XYZ
+pub fn to_rgb(radiance: f32, wavelength: f32) -> Color3 {
+    let (x, y, z) = color_matching(wavelength);
+    let scale: f32 = radiance * (MAX_WAVELENGTH - MIN_WAVELENGTH);
+    Color3 {
+        r: scale * (3.2406 * x - 1.5372 * y - 0.4986 * z) / FLAT_SPECTRUM_RGB[0],
+        g: scale * (-0.9689 * x + 1.8758 * y + 0.0415 * z) / FLAT_SPECTRUM_RGB[1],
+        b: scale * (0.0557 * x - 0.2040 * y + 1.0570 * z) / FLAT_SPECTRUM_RGB[2]
+    }
+}
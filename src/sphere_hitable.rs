@@ -1,19 +1,46 @@
+use rand::prelude::*;
 use crate::vec3::Vec3;
 use crate::ray::Ray;
 use crate::hitable::Hitable;
 use crate::hitable::HitRecord;
+use crate::hitable::Interval;
 use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::onb::Onb;
+use crate::util;
 use core::borrow::Borrow;
 
+// Surface coordinates of a point on the unit sphere
+// (u: angle around the Y axis from X=-1, v: angle from Y=-1)
+pub fn sphere_uv(p: &Vec3) -> (f32, f32) {
+    let theta: f32 = (-p.y).clamp(-1.0, 1.0).acos();
+    let phi: f32 = (-p.z).atan2(p.x) + std::f32::consts::PI;
+    (phi / (2.0 * std::f32::consts::PI), theta / std::f32::consts::PI)
+}
+
+// 1 - cos(theta_max) of the cone containing a sphere from sin^2(theta_max), without cancellation for small spheres
+fn one_minus_cos_theta_max(sin_squared: f32) -> f32 {
+    sin_squared / (1.0 + (1.0 - sin_squared).sqrt())
+}
+
 pub struct SphereHitable {
     pub center: Vec3,
     pub radius: f32,
-    pub material: Box<Material + std::marker::Sync>
+    pub material: Box<dyn Material + std::marker::Sync>
+}
+
+impl SphereHitable {
+    fn hit_record(&self, r: &Ray, t: f32) -> HitRecord<'_> {
+        let p: Vec3 = r.point_at_parameter(t);
+        let normal: Vec3 = &(&p - &self.center) / self.radius;
+        let (u, v) = sphere_uv(&normal);
+        HitRecord{ t, p, normal, u, v, material: self.material.borrow()}
+    }
 }
 
 impl Hitable for SphereHitable {
     #[inline(always)]
-    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
         let oc: Vec3 = &r.origin - &self.center;
         let a : f32  = r.direction.dot(&r.direction);
         let b : f32  = oc.dot(&r.direction);
@@ -26,11 +53,63 @@ impl Hitable for SphereHitable {
         let b2   : bool = t_min < temp2 && temp2 < t_max;
         if discriminant > 0.0 && (b1 || b2) {
             let t: f32  = if b1 { temp1 } else {temp2};
-            let p: Vec3 = r.point_at_parameter(t);
-            let normal: Vec3 = &(&p - &self.center) / self.radius;
-            Some(HitRecord{ t, p, normal, material: self.material.borrow()})
+            Some(self.hit_record(r, t))
         } else {
             None
         }
     }
+
+    // Between the two roots
+    fn intervals(&self, r: &Ray) -> Vec<Interval<'_>> {
+        let oc: Vec3 = &r.origin - &self.center;
+        let a : f32  = r.direction.dot(&r.direction);
+        let b : f32  = oc.dot(&r.direction);
+        let c : f32  = oc.dot(&oc) - self.radius*self.radius;
+        let discriminant: f32 = b * b - a * c;
+        if discriminant > 0.0 {
+            vec![Interval {
+                entry: self.hit_record(r, (-b - discriminant.sqrt()) / a),
+                exit: self.hit_record(r, (-b + discriminant.sqrt()) / a)
+            }]
+        } else {
+            vec![]
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r: Vec3 = Vec3 {x: self.radius, y: self.radius, z: self.radius};
+        Some(Aabb {
+            min: &self.center - &r,
+            max: &self.center + &r
+        })
+    }
+
+    // Directions are sampled uniformly in the cone containing the sphere
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        let dist_squared: f32 = (&self.center - origin).squared_length();
+        let radius_squared: f32 = self.radius * self.radius;
+        if dist_squared <= radius_squared {
+            // Inside the sphere, all directions are sampled uniformly
+            return 1.0 / (4.0 * std::f32::consts::PI);
+        }
+        if self.hit(&Ray {origin: *origin, direction: *direction, time: 0.0, wavelength: None}, 0.0, f32::MAX).is_none() {
+            return 0.0;
+        }
+        1.0 / (2.0 * std::f32::consts::PI * one_minus_cos_theta_max(radius_squared / dist_squared))
+    }
+
+    fn random(&self, rng: &mut rand::rngs::StdRng, origin: &Vec3) -> Vec3 {
+        let direction: Vec3 = &self.center - origin;
+        let dist_squared: f32 = direction.squared_length();
+        let radius_squared: f32 = self.radius * self.radius;
+        if dist_squared <= radius_squared {
+            return util::random_unit_vector(rng);
+        }
+        let r1: f32 = rng.gen();
+        let r2: f32 = rng.gen();
+        let z: f32 = 1.0 - r2 * one_minus_cos_theta_max(radius_squared / dist_squared);
+        let phi: f32 = 2.0 * std::f32::consts::PI * r1;
+        let sin_theta: f32 = (1.0 - z * z).max(0.0).sqrt();
+        Onb::from_w(&direction).local(&Vec3 {x: phi.cos() * sin_theta, y: phi.sin() * sin_theta, z})
+    }
 }
@@ -0,0 +1,83 @@
+use crate::color3::Color3;
+use crate::vec3::Vec3;
+use crate::perlin::Perlin;
+
+pub trait Texture: Send {
+    // Color at surface coordinates (u, v) and hit point p
+    fn value(&self, u: f32, v: f32, p: &Vec3) -> Color3;
+}
+
+pub struct ConstantTexture {
+    pub color: Color3
+}
+
+impl Texture for ConstantTexture {
+    fn value(&self, _u: f32, _v: f32, _p: &Vec3) -> Color3 {
+        self.color
+    }
+}
+
+// 3D checker pattern
+pub struct CheckerTexture {
+    pub odd: Box<dyn Texture + Sync>,
+    pub even: Box<dyn Texture + Sync>,
+    // Number of squares per unit length (times pi)
+    pub scale: f32
+}
+
+impl Texture for CheckerTexture {
+    fn value(&self, u: f32, v: f32, p: &Vec3) -> Color3 {
+        let sines: f32 = (self.scale * p.x).sin() * (self.scale * p.y).sin() * (self.scale * p.z).sin();
+        if sines < 0.0 {
+            self.odd.value(u, v, p)
+        } else {
+            self.even.value(u, v, p)
+        }
+    }
+}
+
+pub enum NoiseStyle {
+    Perlin,
+    Turbulence,
+    Marble
+}
+
+pub struct NoiseTexture {
+    pub perlin: Perlin,
+    pub scale: f32,
+    pub style: NoiseStyle,
+    pub color: Color3
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f32, _v: f32, p: &Vec3) -> Color3 {
+        let scaled: Vec3 = p * self.scale;
+        let intensity: f32 = match self.style {
+            NoiseStyle::Perlin => 0.5 * (1.0 + self.perlin.noise(&scaled)),
+            NoiseStyle::Turbulence => self.perlin.turbulence(&scaled, 7),
+            NoiseStyle::Marble => 0.5 * (1.0 + (scaled.z + 10.0 * self.perlin.turbulence(p, 7)).sin())
+        };
+        &self.color * intensity
+    }
+}
+
+pub struct ImageTexture {
+    pub width: u32,
+    pub height: u32,
+    // Linear colors (top row first)
+    pub pixels: Vec<Color3>
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f32, v: f32, _p: &Vec3) -> Color3 {
+        if self.pixels.is_empty() {
+            return Color3 {r: 0.0, g: 1.0, b: 1.0};
+        }
+        let u: f32 = u.clamp(0.0, 1.0);
+        // NOTE: v = 0 is the bottom of the image
+        let v: f32 = 1.0 - v.clamp(0.0, 1.0);
+        let i: u32 = ((u * self.width as f32) as u32).min(self.width - 1);
+        let j: u32 = ((v * self.height as f32) as u32).min(self.height - 1);
+        self.pixels[(j * self.width + i) as usize]
+    }
+}
@@ -0,0 +1,150 @@
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::hitable::Hitable;
+use crate::hitable::HitRecord;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use crate::cylinder_hitable::azimuth;
+use core::borrow::Borrow;
+
+// Real roots of x^3 + a x^2 + b x + c = 0
+fn solve_cubic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    // Depressed cubic y^3 + p y + q = 0 with x = y - a / 3
+    let p: f64 = b - a * a / 3.0;
+    let q: f64 = 2.0 * a * a * a / 27.0 - a * b / 3.0 + c;
+    let shift: f64 = -a / 3.0;
+    let discriminant: f64 = q * q / 4.0 + p * p * p / 27.0;
+    if discriminant > 0.0 {
+        // One real root by Cardano's formula
+        let s: f64 = discriminant.sqrt();
+        vec![(-q / 2.0 + s).cbrt() + (-q / 2.0 - s).cbrt() + shift]
+    } else if p == 0.0 {
+        vec![shift]
+    } else {
+        // Three real roots by the trigonometric method
+        let m: f64 = 2.0 * (-p / 3.0).sqrt();
+        let theta: f64 = (3.0 * q / (p * m)).clamp(-1.0, 1.0).acos() / 3.0;
+        (0..3).map(|k| m * (theta - 2.0 * std::f64::consts::PI * k as f64 / 3.0).cos() + shift).collect()
+    }
+}
+
+// Real roots of x^2 + b x + c = 0
+fn solve_quadratic(b: f64, c: f64) -> Vec<f64> {
+    let discriminant: f64 = b * b - 4.0 * c;
+    if discriminant < 0.0 {
+        return vec![];
+    }
+    let s: f64 = discriminant.sqrt();
+    vec![(-b - s) / 2.0, (-b + s) / 2.0]
+}
+
+// Real roots of x^4 + a x^3 + b x^2 + c x + d = 0 by Ferrari's method, refined by Newton's method
+fn solve_quartic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    // Depressed quartic y^4 + p y^2 + q y + r = 0 with x = y - a / 4
+    let p: f64 = b - 3.0 * a * a / 8.0;
+    let q: f64 = c - a * b / 2.0 + a * a * a / 8.0;
+    let r: f64 = d - a * c / 4.0 + a * a * b / 16.0 - 3.0 * a * a * a * a / 256.0;
+    let ys: Vec<f64> = if q.abs() < 1e-12 {
+        // Biquadratic
+        solve_quadratic(p, r).into_iter()
+            .filter(|&z| z >= 0.0)
+            .flat_map(|z| vec![-z.sqrt(), z.sqrt()])
+            .collect()
+    } else {
+        // (y^2 + p / 2 + m)^2 = 2 m (y - q / (4 m))^2 by the positive root m of the resolvent cubic
+        let m: f64 = solve_cubic(p, p * p / 4.0 - r, -q * q / 8.0).into_iter().fold(f64::MIN, f64::max);
+        if m <= 0.0 {
+            return vec![];
+        }
+        let s: f64 = (2.0 * m).sqrt();
+        let mut ys: Vec<f64> = solve_quadratic(-s, p / 2.0 + m + s * q / (4.0 * m));
+        ys.extend(solve_quadratic(s, p / 2.0 + m - s * q / (4.0 * m)));
+        ys
+    };
+    ys.into_iter().map(|y| {
+        let mut x: f64 = y - a / 4.0;
+        for _ in 0..2 {
+            let f: f64 = (((x + a) * x + b) * x + c) * x + d;
+            let df: f64 = ((4.0 * x + 3.0 * a) * x + 2.0 * b) * x + c;
+            if df != 0.0 {
+                x -= f / df;
+            }
+        }
+        x
+    }).collect()
+}
+
+// Torus around the Y axis through the center, whose tube of the minor radius goes around the major radius
+pub struct TorusHitable {
+    pub center: Vec3,
+    pub major_radius: f32,
+    pub minor_radius: f32,
+    pub material: Box<dyn Material + std::marker::Sync>
+}
+
+impl Hitable for TorusHitable {
+    // Surface coordinates are the azimuth and the angle around the tube from the outside
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        // NOTE: Solved in double precision along the unit direction from the closest point of the line to the center
+        // to keep coefficients of the quartic small
+        let length: f64 = r.direction.length() as f64;
+        let d: [f64; 3] = [r.direction.x as f64 / length, r.direction.y as f64 / length, r.direction.z as f64 / length];
+        let o: [f64; 3] = [
+            (r.origin.x - self.center.x) as f64,
+            (r.origin.y - self.center.y) as f64,
+            (r.origin.z - self.center.z) as f64
+        ];
+        let shift: f64 = -(o[0] * d[0] + o[1] * d[1] + o[2] * d[2]);
+        let o: [f64; 3] = [o[0] + shift * d[0], o[1] + shift * d[1], o[2] + shift * d[2]];
+        let major_squared: f64 = (self.major_radius as f64).powi(2);
+        let minor_squared: f64 = (self.minor_radius as f64).powi(2);
+        // The line misses the bounding sphere
+        let oo: f64 = o[0] * o[0] + o[1] * o[1] + o[2] * o[2];
+        if oo > (self.major_radius as f64 + self.minor_radius as f64).powi(2) {
+            return None;
+        }
+        // (|P|^2 + R^2 - r^2)^2 = 4 R^2 (Px^2 + Pz^2) at P = o + s d, where |d| = 1 and o.d = 0
+        let k: f64 = oo + major_squared - minor_squared;
+        let roots: Vec<f64> = solve_quartic(
+            0.0,
+            2.0 * k - 4.0 * major_squared * (d[0] * d[0] + d[2] * d[2]),
+            -8.0 * major_squared * (o[0] * d[0] + o[2] * d[2]),
+            k * k - 4.0 * major_squared * (o[0] * o[0] + o[2] * o[2])
+        );
+        let t: f32 = roots.into_iter()
+            .map(|s| ((s + shift) / length) as f32)
+            .filter(|&t| t_min < t && t < t_max)
+            .fold(f32::MAX, f32::min);
+        if t == f32::MAX {
+            return None;
+        }
+        let p: Vec3 = r.point_at_parameter(t);
+        let local: Vec3 = &p - &self.center;
+        // Normal from the closest point of the circle at the core of the tube
+        let axial_distance: f32 = (local.x * local.x + local.z * local.z).sqrt();
+        let core: Vec3 = Vec3 {
+            x: local.x * self.major_radius / axial_distance,
+            y: 0.0,
+            z: local.z * self.major_radius / axial_distance
+        };
+        let normal: Vec3 = (&local - &core).unit_vector();
+        let tube_angle: f32 = normal.y.atan2(axial_distance - self.major_radius);
+        Some(HitRecord {
+            t,
+            p,
+            normal,
+            u: azimuth(local.x, local.z),
+            v: (tube_angle / (2.0 * std::f32::consts::PI)).rem_euclid(1.0),
+            material: self.material.borrow()
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent: f32 = self.major_radius + self.minor_radius;
+        let e: Vec3 = Vec3 {x: extent, y: self.minor_radius, z: extent};
+        Some(Aabb {
+            min: &self.center - &e,
+            max: &self.center + &e
+        })
+    }
+}
@@ -0,0 +1,95 @@
+use std::sync::Arc;
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::hitable::Hitable;
+use crate::hitable::HitRecord;
+use crate::hitable::Interval;
+use crate::aabb::Aabb;
+use crate::mat4::Mat4;
+
+// Instance of a hitable placed by an affine transform, sharing the hitable with other instances
+// NOTE: Rays are transformed into the object space without normalizing directions, so that t is the same in both spaces
+pub struct TransformHitable {
+    hitable: Arc<dyn Hitable + Sync>,
+    // From the object space to the world space
+    matrix: Mat4,
+    inverse: Mat4,
+    // Transpose of the inverse, which transforms normals into the world space
+    normal_matrix: Mat4
+}
+
+impl TransformHitable {
+    // None if the matrix is singular
+    pub fn new(hitable: Arc<dyn Hitable + Sync>, matrix: Mat4) -> Option<TransformHitable> {
+        let inverse: Mat4 = matrix.inverse()?;
+        Some(TransformHitable {
+            hitable,
+            matrix,
+            inverse,
+            normal_matrix: inverse.transpose()
+        })
+    }
+
+    fn object_ray(&self, r: &Ray) -> Ray {
+        Ray {
+            origin: self.inverse.transform_point(&r.origin),
+            direction: self.inverse.transform_vector(&r.direction),
+            time: r.time,
+            wavelength: r.wavelength
+        }
+    }
+
+    // Hit record in the world space from the one in the object space
+    fn world_hit_record<'a>(&self, r: &Ray, hit_record: HitRecord<'a>) -> HitRecord<'a> {
+        HitRecord {
+            p: r.point_at_parameter(hit_record.t),
+            normal: self.normal_matrix.transform_vector(&hit_record.normal).unit_vector(),
+            ..hit_record
+        }
+    }
+}
+
+impl Hitable for TransformHitable {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let hit_record: HitRecord = self.hitable.hit(&self.object_ray(r), t_min, t_max)?;
+        Some(self.world_hit_record(r, hit_record))
+    }
+
+    fn intervals(&self, r: &Ray) -> Vec<Interval<'_>> {
+        self.hitable.intervals(&self.object_ray(r)).into_iter().map(|interval| Interval {
+            entry: self.world_hit_record(r, interval.entry),
+            exit: self.world_hit_record(r, interval.exit)
+        }).collect()
+    }
+
+    // Box around the transformed corners of the box in the object space
+    fn bounding_box(&self) -> Option<Aabb> {
+        let bbox: Aabb = self.hitable.bounding_box()?;
+        let mut transformed_opt: Option<Aabb> = None;
+        for i in 0..8 {
+            let corner: Vec3 = Vec3 {
+                x: if i & 1 == 0 { bbox.min.x } else { bbox.max.x },
+                y: if i & 2 == 0 { bbox.min.y } else { bbox.max.y },
+                z: if i & 4 == 0 { bbox.min.z } else { bbox.max.z }
+            };
+            let p: Vec3 = self.matrix.transform_point(&corner);
+            let point_box: Aabb = Aabb {min: p, max: p};
+            transformed_opt = Some(match transformed_opt {
+                Some(b) => b.surrounding_box(&point_box),
+                None => point_box
+            });
+        }
+        transformed_opt
+    }
+
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        let object_direction: Vec3 = self.inverse.transform_vector(&direction.unit_vector());
+        let pdf: f32 = self.hitable.pdf_value(&self.inverse.transform_point(origin), &object_direction);
+        // Jacobian of the solid angle in the object space by the one in the world space
+        pdf * self.inverse.linear_determinant().abs() / object_direction.length().powi(3)
+    }
+
+    fn random(&self, rng: &mut rand::rngs::StdRng, origin: &Vec3) -> Vec3 {
+        self.matrix.transform_vector(&self.hitable.random(rng, &self.inverse.transform_point(origin)))
+    }
+}
@@ -0,0 +1,126 @@
+use rand::prelude::*;
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::hitable::Hitable;
+use crate::hitable::HitRecord;
+use crate::material::Material;
+use crate::aabb::Aabb;
+use core::borrow::Borrow;
+
+pub struct TriangleHitable {
+    pub vertices: [Vec3; 3],
+    // Per-vertex normals for smooth shading
+    pub normals: Option<[Vec3; 3]>,
+    // Per-vertex surface coordinates
+    pub uvs: Option<[(f32, f32); 3]>,
+    pub material: Box<dyn Material + std::marker::Sync>
+}
+
+// Möller–Trumbore intersection returning t and barycentric coordinates (b1, b2) of vertices 1 and 2
+pub fn hit_triangle(r: &Ray, p0: &Vec3, p1: &Vec3, p2: &Vec3, t_min: f32, t_max: f32) -> Option<(f32, f32, f32)> {
+    let e1: Vec3 = p1 - p0;
+    let e2: Vec3 = p2 - p0;
+    let pvec: Vec3 = r.direction.cross(&e2);
+    let det: f32 = e1.dot(&pvec);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv_det: f32 = 1.0 / det;
+    let tvec: Vec3 = &r.origin - p0;
+    let b1: f32 = tvec.dot(&pvec) * inv_det;
+    if !(0.0..=1.0).contains(&b1) {
+        return None;
+    }
+    let qvec: Vec3 = tvec.cross(&e1);
+    let b2: f32 = r.direction.dot(&qvec) * inv_det;
+    if b2 < 0.0 || b1 + b2 > 1.0 {
+        return None;
+    }
+    let t: f32 = e2.dot(&qvec) * inv_det;
+    if t_min < t && t < t_max {
+        Some((t, b1, b2))
+    } else {
+        None
+    }
+}
+
+// Interpolate values at vertices by barycentric coordinates
+pub fn interpolate(values: [&Vec3; 3], b1: f32, b2: f32) -> Vec3 {
+    &(&(values[0] * (1.0 - b1 - b2)) + &(values[1] * b1)) + &(values[2] * b2)
+}
+
+pub fn interpolate_uv(uvs: [(f32, f32); 3], b1: f32, b2: f32) -> (f32, f32) {
+    let b0: f32 = 1.0 - b1 - b2;
+    (b0 * uvs[0].0 + b1 * uvs[1].0 + b2 * uvs[2].0, b0 * uvs[0].1 + b1 * uvs[1].1 + b2 * uvs[2].1)
+}
+
+// Shading normal from per-vertex normals, kept on the same side as the geometric normal
+pub fn shading_normal(geometric_normal: &Vec3, normals: [&Vec3; 3], b1: f32, b2: f32) -> Vec3 {
+    let n: Vec3 = interpolate(normals, b1, b2).unit_vector();
+    if n.dot(geometric_normal) < 0.0 { -&n } else { n }
+}
+
+pub fn triangle_bounding_box(p0: &Vec3, p1: &Vec3, p2: &Vec3) -> Aabb {
+    // NOTE: Padded so that axis-aligned triangles do not have zero-thickness boxes
+    let pad: Vec3 = Vec3 {x: 1e-4, y: 1e-4, z: 1e-4};
+    let bbox: Aabb = Aabb {min: *p0, max: *p0}
+        .surrounding_box(&Aabb {min: *p1, max: *p1})
+        .surrounding_box(&Aabb {min: *p2, max: *p2});
+    Aabb {
+        min: &bbox.min - &pad,
+        max: &bbox.max + &pad
+    }
+}
+
+impl Hitable for TriangleHitable {
+    fn hit(&self, r: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord<'_>> {
+        let [p0, p1, p2] = &self.vertices;
+        let (t, b1, b2) = hit_triangle(r, p0, p1, p2, t_min, t_max)?;
+        let geometric_normal: Vec3 = (p1 - p0).cross(&(p2 - p0)).unit_vector();
+        let normal: Vec3 = match &self.normals {
+            Some([n0, n1, n2]) => shading_normal(&geometric_normal, [n0, n1, n2], b1, b2),
+            None => geometric_normal
+        };
+        let (u, v) = match self.uvs {
+            Some(uvs) => interpolate_uv(uvs, b1, b2),
+            None => (b1, b2)
+        };
+        Some(HitRecord {
+            t,
+            p: r.point_at_parameter(t),
+            normal,
+            u,
+            v,
+            material: self.material.borrow()
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let [p0, p1, p2] = &self.vertices;
+        Some(triangle_bounding_box(p0, p1, p2))
+    }
+
+    // Points are sampled uniformly on the triangle, converted into the density per solid angle
+    fn pdf_value(&self, origin: &Vec3, direction: &Vec3) -> f32 {
+        let [p0, p1, p2] = &self.vertices;
+        let r: Ray = Ray {origin: *origin, direction: *direction, time: 0.0, wavelength: None};
+        match hit_triangle(&r, p0, p1, p2, 0.0, f32::MAX) {
+            Some((t, _, _)) => {
+                let n: Vec3 = (p1 - p0).cross(&(p2 - p0));
+                let area: f32 = 0.5 * n.length();
+                let dist_squared: f32 = t * t * direction.squared_length();
+                let cosine: f32 = (n.dot(direction) / (n.length() * direction.length())).abs();
+                dist_squared / (cosine * area)
+            },
+            None => 0.0
+        }
+    }
+
+    fn random(&self, rng: &mut rand::rngs::StdRng, origin: &Vec3) -> Vec3 {
+        let [p0, p1, p2] = &self.vertices;
+        let s: f32 = rng.gen::<f32>().sqrt();
+        let b1: f32 = s * (1.0 - rng.gen::<f32>());
+        let b2: f32 = s - b1;
+        &interpolate([p0, p1, p2], b1, b2) - origin
+    }
+}
@@ -11,9 +11,19 @@ pub fn random_in_unit_sphere(rng: &mut rand::rngs::StdRng) -> Vec3 {
     p
 }
 
-pub fn rng_by_seed(seed: u8) -> rand::rngs::StdRng {
-    let seed: [u8; 32] = [seed; 32];
-    rand::SeedableRng::from_seed(seed)
+pub fn rng_by_seed(seed: u64) -> rand::rngs::StdRng {
+    rand::SeedableRng::seed_from_u64(seed)
+}
+
+// Random generator of one sample keyed by the seed, the frame, the pixel and the sample indices.
+// The key is used as the 256-bit key of ChaCha in StdRng as it is, so every sample has its own stream
+// and the result does not depend on the order of rendering.
+pub fn sample_rng(seed: u64, frame: u64, pixel: u64, sample: u64) -> rand::rngs::StdRng {
+    let mut key: [u8; 32] = [0; 32];
+    for (chunk, value) in key.chunks_mut(8).zip(&[seed, frame, pixel, sample]) {
+        chunk.copy_from_slice(&value.to_le_bytes());
+    }
+    rand::SeedableRng::from_seed(key)
 }
 
 pub struct SkipStepIterator<I> {
@@ -43,3 +53,23 @@ pub fn skip_by_step<I: Iterator>(iter: I, skip_step: usize) -> SkipStepIterator<
         idx: 0
     }
 }
+
+// Direction around the Z axis with the density cos(theta) / PI
+pub fn random_cosine_direction(rng: &mut rand::rngs::StdRng) -> Vec3 {
+    let r1: f32 = rng.gen();
+    let r2: f32 = rng.gen();
+    let phi: f32 = 2.0 * std::f32::consts::PI * r1;
+    Vec3 {
+        x: phi.cos() * r2.sqrt(),
+        y: phi.sin() * r2.sqrt(),
+        z: (1.0 - r2).sqrt()
+    }
+}
+
+// Uniformly distributed unit vector
+pub fn random_unit_vector(rng: &mut rand::rngs::StdRng) -> Vec3 {
+    let z: f32 = 1.0 - 2.0 * rng.gen::<f32>();
+    let r: f32 = (1.0 - z * z).max(0.0).sqrt();
+    let phi: f32 = 2.0 * std::f32::consts::PI * rng.gen::<f32>();
+    Vec3 {x: r * phi.cos(), y: r * phi.sin(), z}
+}
@@ -1,4 +1,4 @@
-use std::ops::{Add, Neg, Sub, Mul, Div};
+use std::ops::{Add, Neg, Sub, Mul, Div, Index};
 
 #[derive(Debug, Copy, Clone)]
 pub struct Vec3 {
@@ -73,3 +73,16 @@ impl Div<f32> for &Vec3 {
         Vec3 {x: self.x / rhs, y: self.y / rhs, z: self.z / rhs}
     }
 }
+
+impl Index<usize> for Vec3 {
+    type Output = f32;
+
+    fn index(&self, axis: usize) -> &Self::Output {
+        match axis {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Vec3 index out of range: {}", axis)
+        }
+    }
+}